@@ -0,0 +1,774 @@
+use std::collections::BTreeMap;
+
+/// What checking a line found.
+#[derive(Debug, PartialEq)]
+pub enum Diagnostic {
+    Valid,
+    Incomplete(String),
+    /// A closer that doesn't match the innermost open chunk. Columns count
+    /// characters from 1; `expected` and `opener` are None when no chunk
+    /// was open.
+    Illegal {
+        column: usize,
+        found: char,
+        expected: Option<char>,
+        opener: Option<usize>,
+    },
+}
+
+impl Diagnostic {
+    /// A compiler-style message for an illegal line, or None otherwise.
+    pub fn message(&self, line_number: usize) -> Option<String> {
+        match self {
+            Diagnostic::Illegal {
+                column,
+                found,
+                expected: Some(expected),
+                opener: Some(opener),
+            } => Some(format!(
+                "line {}, col {}: expected '{}' but found '{}' (chunk opened at col {})",
+                line_number, column, expected, found, opener
+            )),
+            Diagnostic::Illegal { column, found, .. } => Some(format!(
+                "line {}, col {}: found '{}' with no open chunk",
+                line_number, column, found
+            )),
+            _ => None,
+        }
+    }
+
+    /// A JSON object describing the line, with null for fields that don't
+    /// apply to its kind.
+    pub fn to_json(&self, line_number: usize, delimiters: &Delimiters) -> String {
+        let null = || "null".to_owned();
+        let (kind, column, found, expected, completion, score) = match self {
+            Diagnostic::Valid => ("valid", null(), null(), null(), null(), null()),
+            Diagnostic::Incomplete(completion) => (
+                "incomplete",
+                null(),
+                null(),
+                null(),
+                json_string(completion),
+                delimiters.completion_score(completion).to_string(),
+            ),
+            Diagnostic::Illegal {
+                column,
+                found,
+                expected,
+                ..
+            } => (
+                "corrupted",
+                column.to_string(),
+                json_string(&found.to_string()),
+                expected.map_or_else(null, |ch| json_string(&ch.to_string())),
+                null(),
+                delimiters.corruption_score(*found).to_string(),
+            ),
+        };
+        format!(
+            "{{\"line\": {}, \"column\": {}, \"kind\": \"{}\", \"found\": {}, \"expected\": {}, \"completion\": {}, \"score\": {}}}",
+            line_number, column, kind, found, expected, completion, score
+        )
+    }
+}
+
+/// Quotes a string for JSON, escaping quotes, backslashes and control
+/// characters.
+fn json_string(s: &str) -> String {
+    let escaped = s
+        .chars()
+        .map(|ch| match ch {
+            '"' => "\\\"".to_owned(),
+            '\\' => "\\\\".to_owned(),
+            ch if ch.is_control() => format!("\\u{:04x}", ch as u32),
+            ch => ch.to_string(),
+        })
+        .collect::<String>();
+    format!("\"{}\"", escaped)
+}
+
+/// A single-character edit to a corrupted line. Columns count characters
+/// from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Repair {
+    Delete {
+        column: usize,
+        found: char,
+    },
+    Replace {
+        column: usize,
+        found: char,
+        with: char,
+    },
+}
+
+impl Repair {
+    pub fn apply(&self, line: &str) -> String {
+        let (column, with) = match self {
+            Repair::Delete { column, .. } => (*column, None),
+            Repair::Replace { column, with, .. } => (*column, Some(*with)),
+        };
+        line.chars()
+            .zip(1..)
+            .filter_map(|(ch, at)| if at == column { with } else { Some(ch) })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Repair::Delete { column, found } => write!(f, "col {}: delete '{}'", column, found),
+            Repair::Replace {
+                column,
+                found,
+                with,
+            } => write!(f, "col {}: replace '{}' with '{}'", column, found, with),
+        }
+    }
+}
+
+/// A repair together with what checking the repaired line found.
+#[derive(Debug, PartialEq)]
+pub struct RepairSuggestion {
+    pub repair: Repair,
+    pub result: Diagnostic,
+}
+
+impl std::fmt::Display for RepairSuggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.result {
+            Diagnostic::Valid => write!(f, "{}, after which the line is valid", self.repair),
+            Diagnostic::Incomplete(_) => {
+                write!(f, "{}, after which the line is incomplete", self.repair)
+            }
+            Diagnostic::Illegal { column, .. } => write!(
+                f,
+                "{}, after which the line is still corrupted at col {}",
+                self.repair, column
+            ),
+        }
+    }
+}
+
+/// A pair of delimiters with the scores for a corrupted or missing closer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delimiter {
+    pub open: char,
+    pub close: char,
+    pub corruption_score: i64,
+    pub completion_score: i64,
+}
+
+impl Delimiter {
+    pub fn new(open: char, close: char, corruption_score: i64, completion_score: i64) -> Self {
+        Delimiter {
+            open,
+            close,
+            corruption_score,
+            completion_score,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delimiters(pub Vec<Delimiter>);
+
+impl Default for Delimiters {
+    /// The puzzle's four pairs.
+    fn default() -> Self {
+        Delimiters(vec![
+            Delimiter::new('(', ')', 3, 1),
+            Delimiter::new('[', ']', 57, 2),
+            Delimiter::new('{', '}', 1197, 3),
+            Delimiter::new('<', '>', 25137, 4),
+        ])
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    /// A line that isn't two delimiters followed by two scores.
+    Malformed {
+        line_number: usize,
+    },
+    BadScore {
+        line_number: usize,
+        score: String,
+    },
+    /// A character already used by an earlier pair.
+    Duplicate {
+        line_number: usize,
+        ch: char,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Malformed { line_number } => write!(
+                f,
+                "Pair on line {} should look like \"() 3 1\"",
+                line_number
+            ),
+            ConfigError::BadScore { line_number, score } => {
+                write!(
+                    f,
+                    "Score {:?} on line {} is not a number",
+                    score, line_number
+                )
+            }
+            ConfigError::Duplicate { line_number, ch } => {
+                write!(f, "'{}' on line {} is already a delimiter", ch, line_number)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Delimiters {
+    type Err = ConfigError;
+
+    /// Reads one pair per line as the opener and closer followed by their
+    /// corruption and completion scores, e.g. `() 3 1`. Blank lines and
+    /// lines starting with `#` are skipped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut pairs: Vec<Delimiter> = Vec::default();
+        for (line, line_number) in s.lines().zip(1..) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (chars, corruption, completion) =
+                match line.split_whitespace().collect::<Vec<_>>()[..] {
+                    [chars, corruption, completion] => (chars, corruption, completion),
+                    _ => return Err(ConfigError::Malformed { line_number }),
+                };
+            let (open, close) = match chars.chars().collect::<Vec<_>>()[..] {
+                [open, close] => (open, close),
+                _ => return Err(ConfigError::Malformed { line_number }),
+            };
+            let score = |score: &str| {
+                score.parse::<i64>().map_err(|_| ConfigError::BadScore {
+                    line_number,
+                    score: score.to_owned(),
+                })
+            };
+            let corruption_score = score(corruption)?;
+            let completion_score = score(completion)?;
+            let used = |ch: char| pairs.iter().any(|d| d.open == ch || d.close == ch);
+            if let Some(ch) = [open, close].into_iter().find(|ch| used(*ch)) {
+                return Err(ConfigError::Duplicate { line_number, ch });
+            }
+            if open == close {
+                return Err(ConfigError::Duplicate {
+                    line_number,
+                    ch: close,
+                });
+            }
+            pairs.push(Delimiter::new(
+                open,
+                close,
+                corruption_score,
+                completion_score,
+            ));
+        }
+        Ok(Delimiters(pairs))
+    }
+}
+
+impl Delimiters {
+    fn closer_for(&self, open: char) -> Option<char> {
+        self.0.iter().find(|d| d.open == open).map(|d| d.close)
+    }
+
+    pub fn corruption_score(&self, close: char) -> i64 {
+        self.0
+            .iter()
+            .find(|d| d.close == close)
+            .map_or(0, |d| d.corruption_score)
+    }
+
+    /// Scores a completion string, treating each closer's score as a digit
+    /// in base one more than the largest completion score.
+    pub fn completion_score(&self, completion: &str) -> i64 {
+        let base = self.0.iter().map(|d| d.completion_score).max().unwrap_or(0) + 1;
+        completion.chars().fold(0_i64, |score, ch| {
+            score * base
+                + self
+                    .0
+                    .iter()
+                    .find(|d| d.close == ch)
+                    .map_or(0, |d| d.completion_score)
+        })
+    }
+}
+
+fn parse_line(s: &str, delimiters: &Delimiters) -> Diagnostic {
+    // The closer each open chunk expects, with the column it opened at.
+    let mut queue: Vec<(char, usize)> = Vec::default();
+    for (c, column) in s.chars().zip(1..) {
+        match delimiters.closer_for(c) {
+            Some(close) => queue.push((close, column)),
+            None => match queue.pop() {
+                Some((q_ch, _)) if q_ch == c => {}
+                open => {
+                    return Diagnostic::Illegal {
+                        column,
+                        found: c,
+                        expected: open.map(|(q_ch, _)| q_ch),
+                        opener: open.map(|(_, opened)| opened),
+                    }
+                }
+            },
+        }
+    }
+    if queue.is_empty() {
+        Diagnostic::Valid
+    } else {
+        let s2: String = queue.iter().rev().map(|(q_ch, _)| q_ch).collect();
+        Diagnostic::Incomplete(s2)
+    }
+}
+
+/// Running totals, kept as lines stream past so only the completion scores
+/// of incomplete lines are held in memory.
+#[derive(Debug, Default)]
+pub struct Scores {
+    pub total_syntax_error_score: i64,
+    /// The completion score of each incomplete line, in no set order.
+    pub incomplete_scores: Vec<i64>,
+    pub by_character: BTreeMap<char, CharacterTally>,
+}
+
+/// How often one closer turned up in corrupted lines and completions.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CharacterTally {
+    /// Lines where it was the first illegal character.
+    pub corrupted: usize,
+    pub corruption_score: i64,
+    /// Times it was added to complete a line.
+    pub completed: usize,
+}
+
+impl Scores {
+    pub fn add(&mut self, result: &Diagnostic, delimiters: &Delimiters) {
+        match result {
+            Diagnostic::Illegal { found, .. } => {
+                let score = delimiters.corruption_score(*found);
+                self.total_syntax_error_score += score;
+                let tally = self.by_character.entry(*found).or_default();
+                tally.corrupted += 1;
+                tally.corruption_score += score;
+            }
+            Diagnostic::Incomplete(st) => {
+                self.incomplete_scores.push(delimiters.completion_score(st));
+                for ch in st.chars() {
+                    self.by_character.entry(ch).or_default().completed += 1;
+                }
+            }
+            Diagnostic::Valid => {}
+        }
+    }
+
+    pub fn mean_score(&self) -> Option<f64> {
+        let count = self.incomplete_scores.len();
+        (count > 0).then(|| self.incomplete_scores.iter().sum::<i64>() as f64 / count as f64)
+    }
+
+    pub fn max_score(&self) -> Option<i64> {
+        self.incomplete_scores.iter().copied().max()
+    }
+
+    /// One line per character with its tallies.
+    pub fn breakdown(&self) -> String {
+        self.by_character
+            .iter()
+            .map(|(ch, tally)| {
+                format!(
+                    "'{}': {} corrupted (score {}), {} completed\n",
+                    ch, tally.corrupted, tally.corruption_score, tally.completed
+                )
+            })
+            .collect()
+    }
+
+    /// The median completion score, or None if no line was incomplete.
+    pub fn middle_score(&mut self) -> Option<i64> {
+        if self.incomplete_scores.is_empty() {
+            return None;
+        }
+        let middle = self.incomplete_scores.len() / 2;
+        Some(*self.incomplete_scores.select_nth_unstable(middle).1)
+    }
+}
+
+/// A summary of the scores to print.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Median,
+    Mean,
+    Max,
+    Breakdown,
+}
+
+impl std::str::FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "median" => Ok(Aggregate::Median),
+            "mean" => Ok(Aggregate::Mean),
+            "max" => Ok(Aggregate::Max),
+            "breakdown" => Ok(Aggregate::Breakdown),
+            _ => Err(format!(
+                "Unknown aggregate {:?}, expected median, mean, max or breakdown",
+                s
+            )),
+        }
+    }
+}
+
+/// Checks lines of chunks against a set of delimiters.
+#[derive(Debug, Default, Clone)]
+pub struct ChunkParser {
+    pub delimiters: Delimiters,
+}
+
+impl ChunkParser {
+    pub fn new(delimiters: Delimiters) -> Self {
+        ChunkParser { delimiters }
+    }
+
+    pub fn check(&self, line: &str) -> Diagnostic {
+        parse_line(line, &self.delimiters)
+    }
+
+    /// The single-character fixes for a corrupted line's first illegal
+    /// closer: replacing it with the expected closer, then deleting it. Lines
+    /// that aren't corrupted have none.
+    pub fn repairs(&self, line: &str) -> Vec<RepairSuggestion> {
+        let (column, found, expected) = match self.check(line) {
+            Diagnostic::Illegal {
+                column,
+                found,
+                expected,
+                ..
+            } => (column, found, expected),
+            _ => return Vec::default(),
+        };
+        let replace = expected.map(|with| Repair::Replace {
+            column,
+            found,
+            with,
+        });
+        replace
+            .into_iter()
+            .chain([Repair::Delete { column, found }])
+            .map(|repair| RepairSuggestion {
+                result: self.check(&repair.apply(line)),
+                repair,
+            })
+            .collect()
+    }
+
+    /// The first repair after which the line is no longer corrupted, or
+    /// failing that the first repair.
+    pub fn suggest_repair(&self, line: &str) -> Option<RepairSuggestion> {
+        let mut repairs = self.repairs(line);
+        let best = repairs
+            .iter()
+            .position(|r| !matches!(r.result, Diagnostic::Illegal { .. }))
+            .unwrap_or(0);
+        (best < repairs.len()).then(|| repairs.swap_remove(best))
+    }
+
+    /// Checks every line and totals their scores.
+    pub fn score_lines<'a>(&self, lines: impl IntoIterator<Item = &'a str>) -> Scores {
+        let mut scores = Scores::default();
+        for line in lines {
+            scores.add(&self.check(line), &self.delimiters);
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse_line() {
+        assert_eq!(
+            parse_line("[({(<(())[]>[[{[]{<()<>>", &Delimiters::default()),
+            Diagnostic::Incomplete("}}]])})]".to_owned())
+        );
+        assert_eq!(
+            parse_line("[(()[<>])]({[<{<<[]>>(", &Delimiters::default()),
+            Diagnostic::Incomplete(")}>]})".to_owned())
+        );
+        assert_eq!(
+            parse_line("{([(<{}[<>[]}>{[]{[(<()>", &Delimiters::default()),
+            Diagnostic::Illegal {
+                column: 13,
+                found: '}',
+                expected: Some(']'),
+                opener: Some(8)
+            }
+        );
+        assert_eq!(
+            parse_line("(((({<>}<{<{<>}{[]{[]{}", &Delimiters::default()),
+            Diagnostic::Incomplete("}}>}>))))".to_owned())
+        );
+        assert_eq!(
+            parse_line("[[<[([]))<([[{}[[()]]]", &Delimiters::default()),
+            Diagnostic::Illegal {
+                column: 9,
+                found: ')',
+                expected: Some(']'),
+                opener: Some(4)
+            }
+        );
+        assert_eq!(
+            parse_line("[{[{({}]{}}([{[{{{}}([]", &Delimiters::default()),
+            Diagnostic::Illegal {
+                column: 8,
+                found: ']',
+                expected: Some(')'),
+                opener: Some(5)
+            }
+        );
+        assert_eq!(
+            parse_line("{<[[]]>}<{[{[{[]{()[[[]", &Delimiters::default()),
+            Diagnostic::Incomplete("]]}}]}]}>".to_owned())
+        );
+        assert_eq!(
+            parse_line("[<(<(<(<{}))><([]([]()", &Delimiters::default()),
+            Diagnostic::Illegal {
+                column: 11,
+                found: ')',
+                expected: Some('>'),
+                opener: Some(8)
+            }
+        );
+        assert_eq!(
+            parse_line("<{([([[(<>()){}]>(<<{{", &Delimiters::default()),
+            Diagnostic::Illegal {
+                column: 17,
+                found: '>',
+                expected: Some(']'),
+                opener: Some(6)
+            }
+        );
+        assert_eq!(
+            parse_line("<{([{{}}[<[[[<>{}]]]>[]]", &Delimiters::default()),
+            Diagnostic::Incomplete("])}>".to_owned())
+        );
+    }
+
+    #[test]
+    fn check_diagnostic() {
+        assert_eq!(
+            parse_line("{([(<{}[<>[]}>{[]{[(<()>", &Delimiters::default()).message(3),
+            Some("line 3, col 13: expected ']' but found '}' (chunk opened at col 8)".to_owned())
+        );
+        assert_eq!(
+            parse_line("()]", &Delimiters::default()).message(1),
+            Some("line 1, col 3: found ']' with no open chunk".to_owned())
+        );
+        assert_eq!(parse_line("(()", &Delimiters::default()).message(1), None);
+        assert_eq!(parse_line("", &Delimiters::default()).message(1), None);
+    }
+
+    #[test]
+    fn check_delimiters_config() {
+        assert_eq!(
+            "() 3 1\n[] 57 2\n# braces\n\n{} 1197 3\n<> 25137 4".parse(),
+            Ok(Delimiters::default())
+        );
+
+        let delimiters: Delimiters = "/\\ 10 1\n|- 20 2".parse().unwrap();
+        assert_eq!(parse_line("/|-\\", &delimiters), Diagnostic::Valid);
+        assert_eq!(
+            parse_line("//|", &delimiters),
+            Diagnostic::Incomplete("-\\\\".to_owned())
+        );
+        assert_eq!(delimiters.completion_score("-\\\\"), 22);
+        let corrupted = parse_line("/|\\", &delimiters);
+        assert_eq!(
+            corrupted,
+            Diagnostic::Illegal {
+                column: 3,
+                found: '\\',
+                expected: Some('-'),
+                opener: Some(2)
+            }
+        );
+        assert_eq!(delimiters.corruption_score('\\'), 10);
+        assert_eq!(
+            parse_line("(", &delimiters),
+            Diagnostic::Illegal {
+                column: 1,
+                found: '(',
+                expected: None,
+                opener: None
+            }
+        );
+
+        assert_eq!(
+            "( 3 1".parse::<Delimiters>(),
+            Err(ConfigError::Malformed { line_number: 1 })
+        );
+        assert_eq!(
+            "() 3 x".parse::<Delimiters>(),
+            Err(ConfigError::BadScore {
+                line_number: 1,
+                score: "x".to_owned()
+            })
+        );
+        assert_eq!(
+            "() 3 1\n[) 5 2".parse::<Delimiters>(),
+            Err(ConfigError::Duplicate {
+                line_number: 2,
+                ch: ')'
+            })
+        );
+        assert_eq!(
+            "|| 3 1".parse::<Delimiters>(),
+            Err(ConfigError::Duplicate {
+                line_number: 1,
+                ch: '|'
+            })
+        );
+    }
+
+    #[test]
+    fn check_scores() {
+        let delimiters = Delimiters::default();
+        let mut scores = Scores::default();
+        for line in [
+            "[({(<(())[]>[[{[]{<()<>>",
+            "[(()[<>])]({[<{<<[]>>(",
+            "{([(<{}[<>[]}>{[]{[(<()>",
+            "(((({<>}<{<{<>}{[]{[]{}",
+            "[[<[([]))<([[{}[[()]]]",
+            "[{[{({}]{}}([{[{{{}}([]",
+            "{<[[]]>}<{[{[{[]{()[[[]",
+            "[<(<(<(<{}))><([]([]()",
+            "<{([([[(<>()){}]>(<<{{",
+            "<{([{{}}[<[[[<>{}]]]>[]]",
+            "[]",
+        ] {
+            scores.add(&parse_line(line, &delimiters), &delimiters);
+        }
+        assert_eq!(scores.total_syntax_error_score, 26397);
+        assert_eq!(scores.incomplete_scores.len(), 5);
+        assert_eq!(scores.middle_score(), Some(288957));
+        assert_eq!(Scores::default().middle_score(), None);
+    }
+
+    #[test]
+    fn check_chunk_parser() {
+        let parser = ChunkParser::default();
+        assert_eq!(
+            parser.check("[({(<(())[]>[[{[]{<()<>>"),
+            Diagnostic::Incomplete("}}]])})]".to_owned())
+        );
+        let mut scores =
+            parser.score_lines(["{([(<{}[<>[]}>{[]{[(<()>", "<{([{{}}[<[[[<>{}]]]>[]]", "()"]);
+        assert_eq!(scores.total_syntax_error_score, 1197);
+        assert_eq!(scores.middle_score(), Some(294));
+    }
+
+    #[test]
+    fn check_repairs() {
+        let parser = ChunkParser::default();
+        assert_eq!(
+            parser.repairs("{([(<{}[<>[]}>{[]{[(<()>"),
+            vec![
+                RepairSuggestion {
+                    repair: Repair::Replace {
+                        column: 13,
+                        found: '}',
+                        with: ']'
+                    },
+                    result: Diagnostic::Incomplete(")]}})])}".to_owned())
+                },
+                RepairSuggestion {
+                    repair: Repair::Delete {
+                        column: 13,
+                        found: '}'
+                    },
+                    result: Diagnostic::Illegal {
+                        column: 13,
+                        found: '>',
+                        expected: Some(']'),
+                        opener: Some(8)
+                    }
+                },
+            ]
+        );
+
+        let suggestion = parser.suggest_repair("(<)").unwrap();
+        assert_eq!(suggestion.repair.apply("(<)"), "(<>");
+        assert_eq!(
+            suggestion.to_string(),
+            "col 3: replace ')' with '>', after which the line is incomplete"
+        );
+        let suggestion = parser.suggest_repair("()]").unwrap();
+        assert_eq!(
+            suggestion.to_string(),
+            "col 3: delete ']', after which the line is valid"
+        );
+        assert_eq!(parser.suggest_repair("(()"), None);
+    }
+
+    #[test]
+    fn check_to_json() {
+        let delimiters = Delimiters::default();
+        assert_eq!(
+            parse_line("{([(<{}[<>[]}>{[]{[(<()>", &delimiters).to_json(3, &delimiters),
+            "{\"line\": 3, \"column\": 13, \"kind\": \"corrupted\", \"found\": \"}\", \"expected\": \"]\", \"completion\": null, \"score\": 1197}"
+        );
+        assert_eq!(
+            parse_line("<{([{{}}[<[[[<>{}]]]>[]]", &delimiters).to_json(1, &delimiters),
+            "{\"line\": 1, \"column\": null, \"kind\": \"incomplete\", \"found\": null, \"expected\": null, \"completion\": \"])}>\", \"score\": 294}"
+        );
+        assert_eq!(
+            Diagnostic::Valid.to_json(2, &delimiters),
+            "{\"line\": 2, \"column\": null, \"kind\": \"valid\", \"found\": null, \"expected\": null, \"completion\": null, \"score\": null}"
+        );
+        assert_eq!(json_string("\"\\\n"), "\"\\\"\\\\\\u000a\"");
+    }
+
+    #[test]
+    fn check_aggregates() {
+        let parser = ChunkParser::default();
+        let mut scores = parser.score_lines([
+            "{([(<{}[<>[]}>{[]{[(<()>",
+            "[[<[([]))<([[{}[[()]]]",
+            "<{([{{}}[<[[[<>{}]]]>[]]",
+            "[(()[<>])]({[<{<<[]>>(",
+        ]);
+        assert_eq!(scores.middle_score(), Some(5566));
+        assert_eq!(scores.mean_score(), Some(2930.0));
+        assert_eq!(scores.max_score(), Some(5566));
+        assert_eq!(
+            scores.by_character[&'}'],
+            CharacterTally {
+                corrupted: 1,
+                corruption_score: 1197,
+                completed: 3
+            }
+        );
+        assert_eq!(
+            scores.breakdown(),
+            "')': 1 corrupted (score 3), 3 completed\n\
+             '>': 0 corrupted (score 0), 2 completed\n\
+             ']': 0 corrupted (score 0), 2 completed\n\
+             '}': 1 corrupted (score 1197), 3 completed\n"
+        );
+        assert_eq!(Scores::default().mean_score(), None);
+        assert_eq!("mean".parse(), Ok(Aggregate::Mean));
+        assert!("mode".parse::<Aggregate>().is_err());
+    }
+}
@@ -1,123 +1,96 @@
 use std::io::{BufRead, BufReader};
+use syntax_scoring::{Aggregate, ChunkParser, Delimiters, Scores};
 
-#[derive(Debug, PartialEq)]
-enum ParseResult {
-    Valid,
-    Incomplete(String),
-    Illegal(char),
-}
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let delimiters = match args.iter().find_map(|a| a.strip_prefix("--pairs=")) {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|err| format!("{}: {}", path, err))?
+            .parse::<Delimiters>()
+            .map_err(|err| format!("{}: {}", path, err))?,
+        None => Delimiters::default(),
+    };
+    let parser = ChunkParser::new(delimiters);
 
-fn parse_line(s: &String) -> ParseResult {
-    let mut queue: Vec<char> = Vec::default();
-    for c in s.chars() {
-        match c {
-            '[' => queue.push(']'),
-            '(' => queue.push(')'),
-            '{' => queue.push('}'),
-            '<' => queue.push('>'),
-            ch => match queue.pop() {
-                Some(q_ch) if q_ch == ch => {}
-                _ => return ParseResult::Illegal(ch),
-            },
+    let diagnostics = args.iter().any(|a| a == "--diagnostics");
+    let repair = args.iter().any(|a| a == "--repair");
+    let json = match args.iter().position(|a| a == "--format") {
+        Some(i) => match args.get(i + 1).map(String::as_str) {
+            Some("json") => true,
+            Some("text") => false,
+            format => {
+                return Err(format!(
+                    "Unknown format {:?}, expected json or text",
+                    format
+                ))
+            }
+        },
+        None => false,
+    };
+    let aggregates = match args.iter().find_map(|a| a.strip_prefix("--aggregate=")) {
+        Some(list) => list
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Aggregate>, _>>()?,
+        None => vec![Aggregate::Median],
+    };
+    if json {
+        println!("[");
+    }
+    let mut scores = Scores::default();
+    for (line, line_number) in BufReader::new(std::io::stdin()).lines().zip(1..) {
+        let line = line.map_err(|err| err.to_string())?;
+        let diagnostic = parser.check(&line);
+        if json {
+            let separator = if line_number > 1 { ",\n" } else { "" };
+            print!(
+                "{}  {}",
+                separator,
+                diagnostic.to_json(line_number, &parser.delimiters)
+            );
+            continue;
         }
+        if diagnostics {
+            if let Some(message) = diagnostic.message(line_number) {
+                println!("{}", message);
+            }
+        }
+        if repair {
+            if let Some(suggestion) = parser.suggest_repair(&line) {
+                println!("line {}, {}", line_number, suggestion);
+            }
+        }
+        scores.add(&diagnostic, &parser.delimiters);
     }
-    if queue.len() == 0 {
-        ParseResult::Valid
-    } else {
-        let s2: String = queue.iter().rev().collect();
-        ParseResult::Incomplete(s2)
+    if json {
+        println!("\n]");
+        return Ok(());
     }
-}
 
-fn main() -> Result<(), String> {
-    let results = BufReader::new(std::io::stdin())
-        .lines()
-        .map(|r| r.map(|s| parse_line(&s)))
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|err| err.to_string())?;
-
-    let mut total_syntax_error_score = 0;
-    let mut incomplete_scores = Vec::default();
-    for r in results {
-        match r {
-            ParseResult::Illegal(ch) => {
-                total_syntax_error_score += match ch {
-                    ')' => 3,
-                    ']' => 57,
-                    '}' => 1197,
-                    '>' => 25137,
-                    _ => 0,
-                }
+    println!(
+        "Total syntax error score: {:?}",
+        scores.total_syntax_error_score
+    );
+    let no_incomplete = "There were no incomplete lines to score";
+    for aggregate in aggregates {
+        match aggregate {
+            Aggregate::Median => {
+                println!(
+                    "Middle score: {:?}",
+                    scores.middle_score().ok_or(no_incomplete)?
+                )
+            }
+            Aggregate::Mean => {
+                println!(
+                    "Mean score: {:.2}",
+                    scores.mean_score().ok_or(no_incomplete)?
+                )
             }
-            ParseResult::Incomplete(st) => {
-                incomplete_scores.push(st.chars().fold(0_i64, |score, ch| {
-                    score * 5
-                        + match ch {
-                            ')' => 1,
-                            ']' => 2,
-                            '}' => 3,
-                            '>' => 4,
-                            _ => 0,
-                        }
-                }))
+            Aggregate::Max => {
+                println!("Max score: {:?}", scores.max_score().ok_or(no_incomplete)?)
             }
-            _ => {}
+            Aggregate::Breakdown => print!("{}", scores.breakdown()),
         }
     }
-
-    incomplete_scores.sort();
-    let middle_score = incomplete_scores[(incomplete_scores.len()) / 2];
-
-    println!("Total syntax error score: {:?}", total_syntax_error_score);
-    println!("Middle score: {:?}", middle_score);
     Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn check_parse_line() {
-        assert_eq!(
-            parse_line(&"[({(<(())[]>[[{[]{<()<>>".to_owned()),
-            ParseResult::Incomplete("}}]])})]".to_owned())
-        );
-        assert_eq!(
-            parse_line(&"[(()[<>])]({[<{<<[]>>(".to_owned()),
-            ParseResult::Incomplete(")}>]})".to_owned())
-        );
-        assert_eq!(
-            parse_line(&"{([(<{}[<>[]}>{[]{[(<()>".to_owned()),
-            ParseResult::Illegal('}')
-        );
-        assert_eq!(
-            parse_line(&"(((({<>}<{<{<>}{[]{[]{}".to_owned()),
-            ParseResult::Incomplete("}}>}>))))".to_owned())
-        );
-        assert_eq!(
-            parse_line(&"[[<[([]))<([[{}[[()]]]".to_owned()),
-            ParseResult::Illegal(')')
-        );
-        assert_eq!(
-            parse_line(&"[{[{({}]{}}([{[{{{}}([]".to_owned()),
-            ParseResult::Illegal(']')
-        );
-        assert_eq!(
-            parse_line(&"{<[[]]>}<{[{[{[]{()[[[]".to_owned()),
-            ParseResult::Incomplete("]]}}]}]}>".to_owned())
-        );
-        assert_eq!(
-            parse_line(&"[<(<(<(<{}))><([]([]()".to_owned()),
-            ParseResult::Illegal(')')
-        );
-        assert_eq!(
-            parse_line(&"<{([([[(<>()){}]>(<<{{".to_owned()),
-            ParseResult::Illegal('>')
-        );
-        assert_eq!(
-            parse_line(&"<{([{{}}[<[[[<>{}]]]>[]]".to_owned()),
-            ParseResult::Incomplete("])}>".to_owned())
-        );
-    }
-}
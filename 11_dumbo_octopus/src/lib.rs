@@ -0,0 +1,337 @@
+/// Draws the grid with the octopuses that just flashed in bold yellow and
+/// the rest dimmed.
+pub fn render(grid: &[Vec<u32>]) -> String {
+    grid.iter()
+        .map(|row| {
+            let cells = row
+                .iter()
+                .map(|energy| match energy {
+                    0 => "\x1b[1;93m0\x1b[0m".to_owned(),
+                    energy => format!("\x1b[2m{}\x1b[0m", energy),
+                })
+                .collect::<String>();
+            format!("{}\n", cells)
+        })
+        .collect()
+}
+
+/// One row per step with the flashes in that step and the running total.
+pub fn timeline_csv(flashes: &[usize]) -> String {
+    let mut csv = String::from("step,flashes_this_step,cumulative_flashes\n");
+    let mut cumulative = 0;
+    for (flashes, step) in flashes.iter().zip(1..) {
+        cumulative += flashes;
+        csv.push_str(&format!("{},{},{}\n", step, flashes, cumulative));
+    }
+    csv
+}
+
+/// What happened in one step of the simulation.
+#[derive(Debug, PartialEq)]
+pub struct StepResult {
+    /// The step number, counting from 1.
+    pub step: usize,
+    pub flashes: usize,
+    /// Whether every octopus flashed.
+    pub synchronized: bool,
+    /// The energies after the step, kept only when snapshots are on.
+    pub snapshot: Option<Vec<Vec<u32>>>,
+}
+
+/// A grid of octopus energies, stepped by iterating over it. Energies are
+/// packed one byte per octopus, row after row.
+#[derive(Debug, Clone)]
+pub struct OctopusGrid {
+    energies: Vec<u8>,
+    width: usize,
+    step: usize,
+    snapshots: bool,
+    /// Octopuses waiting to flash, kept between steps to reuse its space.
+    flash_candidates: Vec<usize>,
+}
+
+impl OctopusGrid {
+    /// Packs rows of energies, which must all be the same width and at most 9.
+    pub fn new(rows: Vec<Vec<u32>>) -> Result<Self, String> {
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut energies = Vec::with_capacity(width * rows.len());
+        for (row, number) in rows.iter().zip(1..) {
+            if row.len() != width {
+                return Err(format!(
+                    "Row {} has {} octopuses but the first row has {}",
+                    number,
+                    row.len(),
+                    width
+                ));
+            }
+            for (energy, column) in row.iter().zip(1..) {
+                match u8::try_from(*energy) {
+                    Ok(energy) if energy <= 9 => energies.push(energy),
+                    _ => {
+                        return Err(format!(
+                            "Row {} column {} has energy {} but the most is 9",
+                            number, column, energy
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(OctopusGrid {
+            energies,
+            width,
+            step: 0,
+            snapshots: false,
+            flash_candidates: Vec::default(),
+        })
+    }
+
+    /// Parses one row of single digit energies per line.
+    pub fn parse(lines: &[String]) -> Result<Self, String> {
+        lines
+            .iter()
+            .map(|s| {
+                s.chars()
+                    .map(|c| c.to_digit(10).ok_or(format!("Non digit found: {}", c)))
+                    .collect::<Result<Vec<u32>, _>>()
+            })
+            .collect::<Result<Vec<Vec<u32>>, _>>()
+            .and_then(OctopusGrid::new)
+    }
+
+    /// Whether each step's result should carry a copy of the grid.
+    pub fn with_snapshots(self, snapshots: bool) -> Self {
+        OctopusGrid { snapshots, ..self }
+    }
+
+    /// A copy of the energies, row by row.
+    pub fn grid(&self) -> Vec<Vec<u32>> {
+        self.energies
+            .chunks(self.width.max(1))
+            .map(|row| row.iter().map(|energy| u32::from(*energy)).collect())
+            .collect()
+    }
+
+    /// The octopuses at zero energy, which are those that flashed in the
+    /// last step.
+    pub fn flashed_count(&self) -> usize {
+        self.energies.iter().filter(|energy| **energy == 0).count()
+    }
+
+    /// Raises an octopus's energy, queueing it to flash when it passes 9.
+    /// An octopus gains at most one from each neighbour, so energies stay
+    /// well within a byte.
+    fn charge(&mut self, i: usize) {
+        self.energies[i] += 1;
+        if self.energies[i] == 10 {
+            self.flash_candidates.push(i);
+        }
+    }
+
+    /// Runs one step and returns how many octopuses flashed.
+    fn take_step(&mut self) -> usize {
+        let height = self.energies.len() / self.width;
+        self.flash_candidates.clear();
+        for i in 0..self.energies.len() {
+            self.charge(i);
+        }
+        while let Some(i) = self.flash_candidates.pop() {
+            let (row, col) = (i / self.width, i % self.width);
+            for n_row in row.saturating_sub(1)..=(row + 1).min(height - 1) {
+                for n_col in col.saturating_sub(1)..=(col + 1).min(self.width - 1) {
+                    if (n_row, n_col) != (row, col) {
+                        self.charge(n_row * self.width + n_col);
+                    }
+                }
+            }
+        }
+
+        let mut flashes = 0;
+        for energy in self.energies.iter_mut().filter(|energy| **energy > 9) {
+            *energy = 0;
+            flashes += 1;
+        }
+        flashes
+    }
+}
+
+impl Iterator for OctopusGrid {
+    type Item = StepResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.energies.is_empty() {
+            return None;
+        }
+        let flashes = self.take_step();
+        self.step += 1;
+        Some(StepResult {
+            step: self.step,
+            flashes,
+            synchronized: flashes == self.energies.len(),
+            snapshot: self.snapshots.then(|| self.grid()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inital_grid() -> Vec<Vec<u32>> {
+        vec![
+            vec![5, 4, 8, 3, 1, 4, 3, 2, 2, 3],
+            vec![2, 7, 4, 5, 8, 5, 4, 7, 1, 1],
+            vec![5, 2, 6, 4, 5, 5, 6, 1, 7, 3],
+            vec![6, 1, 4, 1, 3, 3, 6, 1, 4, 6],
+            vec![6, 3, 5, 7, 3, 8, 5, 4, 7, 8],
+            vec![4, 1, 6, 7, 5, 2, 4, 6, 4, 5],
+            vec![2, 1, 7, 6, 8, 4, 1, 7, 2, 1],
+            vec![6, 8, 8, 2, 8, 8, 1, 1, 3, 4],
+            vec![4, 8, 4, 6, 8, 4, 8, 5, 5, 4],
+            vec![5, 2, 8, 3, 7, 5, 1, 5, 2, 6],
+        ]
+    }
+
+    #[test]
+    fn check_parse_line() {
+        let mut octopuses = OctopusGrid::new(inital_grid()).unwrap();
+
+        octopuses.next();
+        assert_eq!(
+            octopuses.grid(),
+            vec![
+                vec![6, 5, 9, 4, 2, 5, 4, 3, 3, 4],
+                vec![3, 8, 5, 6, 9, 6, 5, 8, 2, 2],
+                vec![6, 3, 7, 5, 6, 6, 7, 2, 8, 4],
+                vec![7, 2, 5, 2, 4, 4, 7, 2, 5, 7],
+                vec![7, 4, 6, 8, 4, 9, 6, 5, 8, 9],
+                vec![5, 2, 7, 8, 6, 3, 5, 7, 5, 6],
+                vec![3, 2, 8, 7, 9, 5, 2, 8, 3, 2],
+                vec![7, 9, 9, 3, 9, 9, 2, 2, 4, 5],
+                vec![5, 9, 5, 7, 9, 5, 9, 6, 6, 5],
+                vec![6, 3, 9, 4, 8, 6, 2, 6, 3, 7],
+            ]
+        );
+
+        octopuses.next();
+        assert_eq!(
+            octopuses.grid(),
+            vec![
+                vec![8, 8, 0, 7, 4, 7, 6, 5, 5, 5],
+                vec![5, 0, 8, 9, 0, 8, 7, 0, 5, 4],
+                vec![8, 5, 9, 7, 8, 8, 9, 6, 0, 8],
+                vec![8, 4, 8, 5, 7, 6, 9, 6, 0, 0],
+                vec![8, 7, 0, 0, 9, 0, 8, 8, 0, 0],
+                vec![6, 6, 0, 0, 0, 8, 8, 9, 8, 9],
+                vec![6, 8, 0, 0, 0, 0, 5, 9, 4, 3],
+                vec![0, 0, 0, 0, 0, 0, 7, 4, 5, 6],
+                vec![9, 0, 0, 0, 0, 0, 0, 8, 7, 6],
+                vec![8, 7, 0, 0, 0, 0, 6, 8, 4, 8],
+            ]
+        );
+
+        octopuses.next();
+        assert_eq!(
+            octopuses.grid(),
+            vec![
+                vec![0, 0, 5, 0, 9, 0, 0, 8, 6, 6],
+                vec![8, 5, 0, 0, 8, 0, 0, 5, 7, 5],
+                vec![9, 9, 0, 0, 0, 0, 0, 0, 3, 9],
+                vec![9, 7, 0, 0, 0, 0, 0, 0, 4, 1],
+                vec![9, 9, 3, 5, 0, 8, 0, 0, 6, 3],
+                vec![7, 7, 1, 2, 3, 0, 0, 0, 0, 0],
+                vec![7, 9, 1, 1, 2, 5, 0, 0, 0, 9],
+                vec![2, 2, 1, 1, 1, 3, 0, 0, 0, 0],
+                vec![0, 4, 2, 1, 1, 2, 5, 0, 0, 0],
+                vec![0, 0, 2, 1, 1, 1, 9, 0, 0, 0],
+            ]
+        );
+
+        octopuses.next();
+        assert_eq!(
+            octopuses.grid(),
+            vec![
+                vec![2, 2, 6, 3, 0, 3, 1, 9, 7, 7],
+                vec![0, 9, 2, 3, 0, 3, 1, 6, 9, 7],
+                vec![0, 0, 3, 2, 2, 2, 1, 1, 5, 0],
+                vec![0, 0, 4, 1, 1, 1, 1, 1, 6, 3],
+                vec![0, 0, 7, 6, 1, 9, 1, 1, 7, 4],
+                vec![0, 0, 5, 3, 4, 1, 1, 1, 2, 2],
+                vec![0, 0, 4, 2, 3, 6, 1, 1, 2, 0],
+                vec![5, 5, 3, 2, 2, 4, 1, 1, 2, 2],
+                vec![1, 5, 3, 2, 2, 4, 7, 2, 1, 1],
+                vec![1, 1, 3, 2, 2, 3, 0, 2, 1, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn check_render() {
+        assert_eq!(
+            render(&[vec![0, 5], vec![9, 0]]),
+            "\x1b[1;93m0\x1b[0m\x1b[2m5\x1b[0m\n\
+             \x1b[2m9\x1b[0m\x1b[1;93m0\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn check_timeline_csv() {
+        let flashes = OctopusGrid::new(inital_grid())
+            .unwrap()
+            .take(3)
+            .map(|result| result.flashes)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            timeline_csv(&flashes),
+            "step,flashes_this_step,cumulative_flashes\n1,0,0\n2,35,35\n3,45,80\n"
+        );
+    }
+
+    #[test]
+    fn check_octopus_grid() {
+        let octopuses = OctopusGrid::new(inital_grid()).unwrap();
+        assert_eq!(octopuses.flashed_count(), 0);
+        assert_eq!(
+            octopuses
+                .clone()
+                .take(100)
+                .map(|result| result.flashes)
+                .sum::<usize>(),
+            1656
+        );
+        let first_sync = octopuses
+            .clone()
+            .find(|result| result.synchronized)
+            .map(|result| result.step);
+        assert_eq!(first_sync, Some(195));
+
+        let mut octopuses = octopuses.with_snapshots(true);
+        let first = octopuses.next().unwrap();
+        assert_eq!(first.snapshot, Some(octopuses.grid()));
+        assert_eq!(first.step, 1);
+        assert_eq!(OctopusGrid::new(vec![]).unwrap().next(), None);
+        assert_eq!(
+            OctopusGrid::new(vec![vec![1, 2], vec![3, 255]]).err(),
+            Some("Row 2 column 2 has energy 255 but the most is 9".to_owned())
+        );
+        assert!(OctopusGrid::new(vec![vec![10]]).is_err());
+        assert!(OctopusGrid::parse(&["12".to_owned(), "3x".to_owned()]).is_err());
+        assert_eq!(
+            OctopusGrid::parse(&["12".to_owned(), "3".to_owned()]).err(),
+            Some("Row 2 has 1 octopuses but the first row has 2".to_owned())
+        );
+    }
+
+    #[test]
+    fn check_octopus_grid_edges() {
+        let mut single = OctopusGrid::new(vec![vec![9]]).unwrap();
+        assert_eq!(single.next().map(|result| result.synchronized), Some(true));
+
+        let mut row = OctopusGrid::new(vec![vec![9, 8, 1, 8, 9]]).unwrap();
+        assert_eq!(row.next().map(|result| result.flashes), Some(4));
+        assert_eq!(row.grid(), vec![vec![0, 0, 4, 0, 0]]);
+
+        let large = OctopusGrid::new(vec![vec![5; 1000]; 1000]).unwrap();
+        let first_sync = large.take(10).find(|result| result.synchronized);
+        assert_eq!(first_sync.map(|result| result.step), Some(5));
+    }
+}
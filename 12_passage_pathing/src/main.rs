@@ -2,55 +2,319 @@ use petgraph::{
     graphmap::{GraphMap, UnGraphMap},
     EdgeType,
 };
-use std::io::{BufRead, BufReader};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
 
 fn is_small_cave(name: &str) -> bool {
     name.to_lowercase() == name
 }
 
-fn get_paths(lines: &[(String, String)]) -> Vec<(String, bool)> {
+fn build_caves(lines: &[(String, String)]) -> UnGraphMap<&str, ()> {
     let mut caves = UnGraphMap::<&str, ()>::default();
-    let start = caves.add_node("start");
-    let end = caves.add_node("end");
+    caves.add_node("start");
+    caves.add_node("end");
 
     for line in lines {
         caves.add_edge(&line.0, &line.1, ());
     }
+    caves
+}
+
+/// Every path, with whether it visits a small cave twice.
+#[cfg(test)]
+fn get_paths(lines: &[(String, String)]) -> Vec<(String, bool)> {
+    let caves = build_caves(lines);
+    paths(&caves).collect()
+}
 
-    traverse_graph(&caves, start, start, end, false, &[])
+/// Lazily yields each path from start to end as it is found, with whether
+/// it visits a small cave twice.
+fn paths<'g>(caves: &'g UnGraphMap<&str, ()>) -> impl Iterator<Item = (String, bool)> + 'g {
+    let mut walk = PathWalk::new(caves, "start", "end");
+    std::iter::from_fn(move || {
+        walk.next_path()
+            .map(|(path, double_visits_taken)| (path.join(","), double_visits_taken))
+    })
 }
 
-fn traverse_graph<T: EdgeType>(
-    caves: &GraphMap<&str, (), T>,
-    node: &str,
-    start: &str,
-    end: &str,
-    double_visits_taken: bool,
-    already_visited: &[&str],
-) -> Vec<(String, bool)> {
-    if node == end {
-        return vec![(end.to_owned(), double_visits_taken)];
+/// The number of paths without a small cave visited twice, and the number
+/// with at most one small cave visited twice.
+fn count_paths(lines: &[(String, String)]) -> (usize, usize) {
+    let caves = build_caves(lines);
+    let mut walk = PathWalk::new(&caves, "start", "end");
+    let (mut single, mut total) = (0, 0);
+    while let Some((_, double_visits_taken)) = walk.next_path() {
+        if !double_visits_taken {
+            single += 1;
+        }
+        total += 1;
     }
-    let visited = if is_small_cave(node) {
-        let mut update_vis = already_visited.to_owned();
-        update_vis.push(node);
-        update_vis
-    } else {
-        already_visited.to_vec()
-    };
+    (single, total)
+}
 
-    caves
-        .neighbors(node)
-        .filter(|&n| n != start)
-        .filter(|n| !(double_visits_taken && visited.contains(n)))
-        .flat_map(|n| {
-            let double_visits_taken = double_visits_taken || visited.contains(&n);
-
-            traverse_graph(caves, n, start, end, double_visits_taken, &visited)
-                .into_iter()
-                .map(|(rn, rt)| (format!("{},{}", node, rn), rt))
+/// Path lengths, counted in passages, and how often each cave is visited
+/// across every path with at most one small cave visited twice.
+#[derive(Debug, Default, PartialEq)]
+struct PathStats {
+    shortest: Option<usize>,
+    longest: Option<usize>,
+    /// The number of paths of each length.
+    lengths: BTreeMap<usize, usize>,
+    visits: BTreeMap<String, usize>,
+}
+
+impl PathStats {
+    fn add(&mut self, path: &[&str]) {
+        let length = path.len() - 1;
+        self.shortest = Some(self.shortest.map_or(length, |s| s.min(length)));
+        self.longest = Some(self.longest.map_or(length, |l| l.max(length)));
+        *self.lengths.entry(length).or_default() += 1;
+        for cave in path {
+            match self.visits.get_mut(*cave) {
+                Some(count) => *count += 1,
+                None => {
+                    self.visits.insert(cave.to_string(), 1);
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PathStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (shortest, longest) = match (self.shortest, self.longest) {
+            (Some(shortest), Some(longest)) => (shortest, longest),
+            _ => return writeln!(f, "There are no paths"),
+        };
+        writeln!(f, "Shortest path: {} passages", shortest)?;
+        writeln!(f, "Longest path: {} passages", longest)?;
+        writeln!(f, "Paths by length:")?;
+        for (length, count) in &self.lengths {
+            writeln!(f, "  {}: {}", length, count)?;
+        }
+        writeln!(f, "Visits by cave:")?;
+        for (cave, count) in &self.visits {
+            writeln!(f, "  {}: {}", cave, count)?;
+        }
+        Ok(())
+    }
+}
+
+fn path_stats(lines: &[(String, String)]) -> PathStats {
+    let caves = build_caves(lines);
+    let mut walk = PathWalk::new(&caves, "start", "end");
+    let mut stats = PathStats::default();
+    while let Some((path, _)) = walk.next_path() {
+        stats.add(path);
+    }
+    stats
+}
+
+/// The caves by index, with a bit for each small cave, for counting paths
+/// without walking them.
+struct CaveMasks {
+    neighbours: Vec<Vec<usize>>,
+    /// The bit for each small cave, or None for big caves.
+    bits: Vec<Option<u64>>,
+    start: usize,
+    end: usize,
+}
+
+impl CaveMasks {
+    fn new(lines: &[(String, String)]) -> Result<Self, String> {
+        let caves = build_caves(lines);
+        let names = caves.nodes().collect::<Vec<_>>();
+        let index = |name: &str| names.iter().position(|n| *n == name);
+        let mut small_count = 0;
+        let bits = names
+            .iter()
+            .map(|name| {
+                if !is_small_cave(name) {
+                    return Ok(None);
+                }
+                small_count += 1;
+                1_u64
+                    .checked_shl(small_count - 1)
+                    .map(Some)
+                    .ok_or("More than 64 small caves to count paths through")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // build_caves always adds start and end, so both are found.
+        Ok(CaveMasks {
+            neighbours: names
+                .iter()
+                .map(|name| caves.neighbors(name).filter_map(index).collect())
+                .collect(),
+            bits,
+            start: index("start").unwrap_or_default(),
+            end: index("end").unwrap_or_default(),
         })
-        .collect()
+    }
+
+    /// The paths from `cave` to the end that avoid the small caves in
+    /// `visited`, or may revisit one of them if the budget isn't yet used.
+    fn count_from(
+        &self,
+        cave: usize,
+        visited: u64,
+        budget_used: bool,
+        memo: &mut HashMap<(usize, u64, bool), usize>,
+    ) -> usize {
+        if cave == self.end {
+            return 1;
+        }
+        if let Some(count) = memo.get(&(cave, visited, budget_used)) {
+            return *count;
+        }
+        let mut count = 0;
+        for &n in self.neighbours[cave].iter().filter(|&&n| n != self.start) {
+            count += match self.bits[n] {
+                Some(bit) if visited & bit != 0 => {
+                    if budget_used {
+                        continue;
+                    }
+                    self.count_from(n, visited, true, memo)
+                }
+                Some(bit) => self.count_from(n, visited | bit, budget_used, memo),
+                None => self.count_from(n, visited, budget_used, memo),
+            };
+        }
+        memo.insert((cave, visited, budget_used), count);
+        count
+    }
+}
+
+/// The same totals as `count_paths`, found by memoising the number of paths
+/// from each cave for each set of visited small caves.
+fn count_paths_dp(lines: &[(String, String)]) -> Result<(usize, usize), String> {
+    let masks = CaveMasks::new(lines)?;
+    let visited = masks.bits[masks.start].unwrap_or_default();
+    let mut memo = HashMap::default();
+    Ok((
+        masks.count_from(masks.start, visited, true, &mut memo),
+        masks.count_from(masks.start, visited, false, &mut memo),
+    ))
+}
+
+/// The totals from `count_paths_dp`, or from walking every path with
+/// `count_paths` where there are too many small caves to memoise.
+fn count_paths_fast(lines: &[(String, String)]) -> (usize, usize) {
+    count_paths_dp(lines).unwrap_or_else(|_| count_paths(lines))
+}
+
+/// Walks every path from start to end depth first, keeping the current path
+/// on one vector and the caves still to explore on a stack. Each stack entry
+/// records the depth it sits at, so the path can be cut back to it.
+struct PathWalk<'g, 'a, T: EdgeType> {
+    caves: &'g GraphMap<&'a str, (), T>,
+    start: &'a str,
+    end: &'a str,
+    path: Vec<&'a str>,
+    stack: Vec<(&'a str, usize, bool)>,
+}
+
+impl<'g, 'a, T: EdgeType> PathWalk<'g, 'a, T> {
+    fn new(caves: &'g GraphMap<&'a str, (), T>, start: &'a str, end: &'a str) -> Self {
+        PathWalk {
+            caves,
+            start,
+            end,
+            path: Vec::default(),
+            stack: vec![(start, 0, false)],
+        }
+    }
+
+    /// The next complete path, with whether it visits a small cave twice.
+    fn next_path(&mut self) -> Option<(&[&'a str], bool)> {
+        while let Some((node, depth, double_visits_taken)) = self.stack.pop() {
+            self.path.truncate(depth);
+            self.path.push(node);
+            if node == self.end {
+                return Some((&self.path, double_visits_taken));
+            }
+            let path = &self.path;
+            let visited = |n: &str| is_small_cave(n) && path.contains(&n);
+
+            let next = self
+                .caves
+                .neighbors(node)
+                .filter(|&n| n != self.start)
+                .filter(|n| !(double_visits_taken && visited(n)))
+                .map(|n| (n, depth + 1, double_visits_taken || visited(n)))
+                .collect::<Vec<_>>();
+            // Pushed in reverse so neighbours are explored in graph order.
+            self.stack.extend(next.into_iter().rev());
+        }
+        None
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CaveError {
+    Missing(&'static str),
+    /// A passage from a cave back to itself. Lines count from 1.
+    SelfLoop {
+        line_number: usize,
+        cave: String,
+    },
+    /// Two big caves joined directly, which could be walked between forever.
+    BigCaveCycle {
+        line_number: usize,
+        a: String,
+        b: String,
+    },
+    EmptyName {
+        line_number: usize,
+    },
+}
+
+impl std::fmt::Display for CaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaveError::Missing(cave) => write!(f, "No passage leads to or from {}", cave),
+            CaveError::SelfLoop { line_number, cave } => {
+                write!(f, "Line {} joins {} to itself", line_number, cave)
+            }
+            CaveError::BigCaveCycle { line_number, a, b } => write!(
+                f,
+                "Line {} joins big caves {} and {}, giving endless paths",
+                line_number, a, b
+            ),
+            CaveError::EmptyName { line_number } => {
+                write!(f, "Line {} has a cave with no name", line_number)
+            }
+        }
+    }
+}
+
+/// Checks that start and end are both connected and no passage would let a
+/// path go on forever.
+fn validate_caves(lines: &[(String, String)]) -> Result<(), CaveError> {
+    for ((a, b), line_number) in lines.iter().zip(1..) {
+        if a.is_empty() || b.is_empty() {
+            return Err(CaveError::EmptyName { line_number });
+        }
+        if a == b {
+            return Err(CaveError::SelfLoop {
+                line_number,
+                cave: a.clone(),
+            });
+        }
+        if !is_small_cave(a) && !is_small_cave(b) {
+            return Err(CaveError::BigCaveCycle {
+                line_number,
+                a: a.clone(),
+                b: b.clone(),
+            });
+        }
+    }
+    for cave in ["start", "end"] {
+        if !lines.iter().any(|(a, b)| a == cave || b == cave) {
+            return Err(CaveError::Missing(cave));
+        }
+    }
+    Ok(())
 }
 
 fn create_parts(s: String) -> Result<(String, String), String> {
@@ -65,15 +329,46 @@ fn main() -> Result<(), String> {
         .lines()
         .map(|r| r.map_err(|e| e.to_string()).and_then(create_parts))
         .collect::<Result<Vec<(String, String)>, _>>()?;
+    validate_caves(&lines).map_err(|err| err.to_string())?;
 
-    let paths = get_paths(&lines);
-    println!(
-        "Paths without a small cave double visit: {:?}",
-        paths.iter().filter(|(_, a)| !a).count()
-    );
+    let args: Vec<String> = std::env::args().collect();
+    let mut listed = None;
+    if let Some(i) = args.iter().position(|a| a == "--list-paths") {
+        let caves = build_caves(&lines);
+        let mut out: Box<dyn Write> = match args.get(i + 1).filter(|a| !a.starts_with("--")) {
+            Some(path) => Box::new(BufWriter::new(
+                File::create(path).map_err(|err| format!("{}: {}", path, err))?,
+            )),
+            None => Box::new(BufWriter::new(std::io::stdout().lock())),
+        };
+        let (mut single, mut total) = (0, 0);
+        for (path, double_visits_taken) in paths(&caves) {
+            writeln!(out, "{}", path).map_err(|err| err.to_string())?;
+            if !double_visits_taken {
+                single += 1;
+            }
+            total += 1;
+        }
+        out.flush().map_err(|err| err.to_string())?;
+        listed = Some((single, total));
+    } else if args.iter().any(|a| a == "--cross-check") {
+        listed = Some(count_paths(&lines));
+    }
+    let (single, total) = count_paths_fast(&lines);
+    if let Some(listed) = listed.filter(|listed| *listed != (single, total)) {
+        return Err(format!(
+            "Walking the paths found {:?} but counting them found {:?}",
+            listed,
+            (single, total)
+        ));
+    }
+    if args.iter().any(|a| a == "--stats") {
+        print!("{}", path_stats(&lines));
+    }
+    println!("Paths without a small cave double visit: {:?}", single);
     println!(
         "Paths with single small cave double visit allowed: {:?}",
-        paths.len()
+        total
     );
 
     Ok(())
@@ -107,6 +402,8 @@ mod tests {
 
         part_1.sort();
         part_2.sort();
+        assert_eq!(count_paths(&lines), (part_1.len(), part_2.len()));
+        assert_eq!(count_paths_dp(&lines), Ok((part_1.len(), part_2.len())));
 
         assert_eq!(
             part_1,
@@ -166,4 +463,119 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn check_deep_cave_system() {
+        let names = (0..5_000).map(|i| format!("c{}", i)).collect::<Vec<_>>();
+        let lines = std::iter::once("start")
+            .chain(names.iter().map(String::as_str))
+            .chain(std::iter::once("end"))
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|pair| (pair[0].to_owned(), pair[1].to_owned()))
+            .collect::<Vec<_>>();
+
+        let paths = get_paths(&lines);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(count_paths(&lines), (1, 1));
+        assert_eq!(
+            count_paths_dp(&lines),
+            Err("More than 64 small caves to count paths through".to_owned())
+        );
+        assert_eq!(count_paths_fast(&lines), (1, 1));
+        assert!(!paths[0].1);
+        assert!(paths[0].0.starts_with("start,c0,c1,"));
+        assert!(paths[0].0.ends_with(",c4999,end"));
+    }
+
+    #[test]
+    fn check_paths_are_lazy() {
+        let lines = [("start", "A"), ("A", "b"), ("A", "end"), ("b", "end")]
+            .map(|(a, b)| (a.to_owned(), b.to_owned()));
+        let caves = build_caves(&lines);
+        let mut found = paths(&caves);
+        assert_eq!(found.next(), Some(("start,A,b,A,b,A,end".to_owned(), true)));
+        assert_eq!(found.count(), 4);
+        assert_eq!(count_paths(&lines), (3, 5));
+        assert_eq!(count_paths_dp(&lines), Ok((3, 5)));
+        assert_eq!(count_paths_fast(&lines), (3, 5));
+    }
+
+    #[test]
+    fn check_count_paths_dp() {
+        let larger = "dc-end HN-start start-kj dc-start dc-HN LN-dc HN-end kj-sa kj-HN kj-dc";
+        let lines = larger
+            .split(' ')
+            .map(|edge| create_parts(edge.to_owned()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(count_paths_dp(&lines), Ok((19, 103)));
+        assert_eq!(count_paths(&lines), (19, 103));
+    }
+
+    #[test]
+    fn check_validate_caves() {
+        let edges = |text: &str| {
+            text.split(' ')
+                .map(|edge| create_parts(edge.to_owned()).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(validate_caves(&edges("start-A A-b b-end")), Ok(()));
+        assert_eq!(
+            validate_caves(&edges("start-A A-b")),
+            Err(CaveError::Missing("end"))
+        );
+        assert_eq!(
+            validate_caves(&edges("A-b b-end")),
+            Err(CaveError::Missing("start"))
+        );
+        assert_eq!(
+            validate_caves(&edges("start-A b-b A-end")),
+            Err(CaveError::SelfLoop {
+                line_number: 2,
+                cave: "b".to_owned()
+            })
+        );
+        assert_eq!(
+            validate_caves(&edges("start-A A-B B-end")),
+            Err(CaveError::BigCaveCycle {
+                line_number: 2,
+                a: "A".to_owned(),
+                b: "B".to_owned()
+            })
+        );
+        assert_eq!(
+            validate_caves(&edges("start-A A- A-end")),
+            Err(CaveError::EmptyName { line_number: 2 })
+        );
+        assert_eq!(
+            CaveError::Missing("end").to_string(),
+            "No passage leads to or from end"
+        );
+    }
+
+    #[test]
+    fn check_path_stats() {
+        let lines = [("start", "A"), ("A", "b"), ("A", "end"), ("b", "end")]
+            .map(|(a, b)| (a.to_owned(), b.to_owned()));
+        let stats = path_stats(&lines);
+        assert_eq!(stats.shortest, Some(2));
+        assert_eq!(stats.longest, Some(6));
+        assert_eq!(
+            stats.to_string(),
+            "Shortest path: 2 passages\n\
+             Longest path: 6 passages\n\
+             Paths by length:\n  \
+               2: 1\n  \
+               3: 1\n  \
+               4: 1\n  \
+               5: 1\n  \
+               6: 1\n\
+             Visits by cave:\n  \
+               A: 9\n  \
+               b: 6\n  \
+               end: 5\n  \
+               start: 5\n"
+        );
+        assert_eq!(PathStats::default().to_string(), "There are no paths\n");
+    }
 }
@@ -0,0 +1,627 @@
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    num::ParseIntError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FoldAlong {
+    X(u16),
+    Y(u16),
+}
+
+pub type Point = (u16, u16);
+
+/// The dots on the sheet, each held once.
+pub type Sheet = HashSet<Point>;
+
+fn parse(lines: Vec<String>) -> Result<(Vec<Point>, Vec<FoldAlong>), String> {
+    let mut points: Vec<Point> = Vec::default();
+    let mut folds: Vec<FoldAlong> = Vec::default();
+
+    let mut iter = lines.into_iter();
+    for val in iter.by_ref() {
+        if val.is_empty() {
+            break;
+        }
+        let parts = val
+            .split(',')
+            .map(|s| s.parse())
+            .collect::<Result<Vec<u16>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        match parts[..] {
+            [a, b] => points.push((a, b)),
+            _ => return Err(format!("Could not parse point from '{}'", val)),
+        }
+    }
+    for val in iter {
+        let parts = val.split('=').collect::<Vec<&str>>();
+
+        let (f_action, amount) = match parts[..] {
+            [a, b] => (a, b),
+            _ => return Err(format!("Could not parse fold from '{}'", val)),
+        };
+
+        let amount: u16 = amount.parse().map_err(|e: ParseIntError| e.to_string())?;
+
+        match f_action {
+            "fold along x" => folds.push(FoldAlong::X(amount)),
+            "fold along y" => folds.push(FoldAlong::Y(amount)),
+            _ => return Err(format!("Could not parse fold instruction '{}'", f_action)),
+        }
+    }
+
+    Ok((points, folds))
+}
+
+fn fold_points(fold: &FoldAlong, points: &Sheet) -> Sheet {
+    match *fold {
+        FoldAlong::X(x) => points
+            .iter()
+            .map(|p| (if p.0 > x { 2 * x - p.0 } else { p.0 }, p.1))
+            .collect(),
+        FoldAlong::Y(y) => points
+            .iter()
+            .map(|p| (p.0, if p.1 > y { 2 * y - p.1 } else { p.1 }))
+            .collect(),
+    }
+}
+
+/// The characters drawn for a dot and for a blank part of the sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyphs {
+    pub dot: char,
+    pub blank: char,
+}
+
+impl Glyphs {
+    pub const ASCII: Glyphs = Glyphs {
+        dot: '#',
+        blank: '.',
+    };
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Glyphs {
+            dot: '🌍',
+            blank: '🦙',
+        }
+    }
+}
+
+impl std::str::FromStr for Glyphs {
+    type Err = String;
+
+    /// Reads the dot then the blank, e.g. `#.`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.chars().collect::<Vec<_>>()[..] {
+            [dot, blank] => Ok(Glyphs { dot, blank }),
+            _ => Err(format!(
+                "Glyphs {:?} should be two characters, the dot then the blank",
+                s
+            )),
+        }
+    }
+}
+
+/// The largest x and y of any point.
+fn sheet_extent(points: &Sheet) -> Point {
+    points.iter().fold((0, 0), |agg, p| {
+        (cmp::max(p.0, agg.0), cmp::max(p.1, agg.1))
+    })
+}
+
+/// The widest and tallest sheet `--show-steps` will draw.
+const MAX_STEP_DRAWING: Point = (100, 60);
+
+/// The drawing of a sheet part way through folding, or a note of its size
+/// when it is too big to show.
+fn step_drawing(points: &Sheet, glyphs: Glyphs) -> Vec<String> {
+    let (max_x, max_y) = sheet_extent(points);
+    if max_x >= MAX_STEP_DRAWING.0 || max_y >= MAX_STEP_DRAWING.1 {
+        vec![format!(
+            "The sheet is {} by {}, too large to show",
+            u32::from(max_x) + 1,
+            u32::from(max_y) + 1
+        )]
+    } else {
+        generate_drawing(points, glyphs)
+    }
+}
+
+fn generate_drawing(points: &Sheet, glyphs: Glyphs) -> Vec<String> {
+    let (max_x, max_y) = sheet_extent(points);
+
+    (0..=max_y)
+        .map(|y| {
+            (0..=max_x).fold(String::with_capacity(usize::from(max_x)), |mut s, x| {
+                if points.contains(&(x, y)) {
+                    s.push(glyphs.dot)
+                } else {
+                    s.push(glyphs.blank)
+                }
+                s
+            })
+        })
+        .collect()
+}
+
+/// How many frames `Origami::fold_frames` takes for a fold by default.
+pub const FOLD_FRAMES: usize = 8;
+
+/// Where a coordinate on the far side of a fold line sits a fraction `t` of
+/// the way to its mirrored place.
+fn slide(from: u16, line: u16, t: f64) -> u16 {
+    if from <= line {
+        return from;
+    }
+    let to = 2.0 * f64::from(line) - f64::from(from);
+    (f64::from(from) + (to - f64::from(from)) * t)
+        .round()
+        .max(0.0) as u16
+}
+
+/// Draws the sheet a fraction `t` of the way through a fold, with the
+/// points that stay put in green, the points sliding over in cyan and the
+/// fold line picked out in yellow.
+fn fold_frame(points: &Sheet, fold: FoldAlong, t: f64, extent: Point, glyphs: Glyphs) -> String {
+    let mut moved: HashMap<Point, bool> = HashMap::new();
+    for &(x, y) in points {
+        let (to, sliding) = match fold {
+            FoldAlong::X(line) => ((slide(x, line, t), y), x > line),
+            FoldAlong::Y(line) => ((x, slide(y, line, t)), y > line),
+        };
+        *moved.entry(to).or_insert(sliding) &= sliding;
+    }
+
+    let mut frame = String::new();
+    for y in 0..=extent.1 {
+        for x in 0..=extent.0 {
+            let on_line = match fold {
+                FoldAlong::X(line) => x == line,
+                FoldAlong::Y(line) => y == line,
+            };
+            match moved.get(&(x, y)) {
+                Some(true) => frame.push_str(&format!("\x1b[1;96m{}\x1b[0m", glyphs.dot)),
+                Some(false) => frame.push_str(&format!("\x1b[1;92m{}\x1b[0m", glyphs.dot)),
+                None if on_line => frame.push_str(&format!("\x1b[43m{}\x1b[0m", glyphs.blank)),
+                None => frame.push(glyphs.blank),
+            }
+        }
+        frame.push('\n');
+    }
+    frame
+}
+
+/// The letters of the puzzle's font, four dots wide and six high, with a
+/// blank column between letters.
+const LETTERS: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the folded points as a row of letters, or None if any part of the
+/// sheet isn't a letter of the font.
+fn read_letters(points: &Sheet) -> Option<String> {
+    let max_x = points.iter().map(|p| p.0).max()?;
+    if points.iter().any(|p| p.1 >= 6) {
+        return None;
+    }
+    let count = (usize::from(max_x) + 5) / 5;
+    (0..count)
+        .map(|i| {
+            let left = u16::try_from(i * 5).ok()?;
+            let glyph = (0..6)
+                .map(|y| {
+                    (left..left + 4)
+                        .map(|x| if points.contains(&(x, y)) { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            let gap_is_blank = points.iter().all(|p| p.0 != left + 4);
+            LETTERS
+                .iter()
+                .find(|(_, rows)| gap_is_blank && rows[..] == glyph[..])
+                .map(|(letter, _)| *letter)
+        })
+        .collect()
+}
+
+/// A transparent sheet and the folds still to be made in it.
+#[derive(Debug, Clone)]
+pub struct Origami {
+    sheet: Sheet,
+    folds: Vec<FoldAlong>,
+    /// How many of the folds have been made.
+    folded: usize,
+}
+
+impl Origami {
+    /// Reads the points, a blank line, then the fold instructions.
+    pub fn from_input(lines: Vec<String>) -> Result<Self, String> {
+        let (points, folds) = parse(lines)?;
+        Ok(Origami {
+            sheet: points.into_iter().collect(),
+            folds,
+            folded: 0,
+        })
+    }
+
+    /// Makes the next fold and returns it, or None once every fold is made.
+    pub fn fold_next(&mut self) -> Option<FoldAlong> {
+        let fold = *self.folds.get(self.folded)?;
+        self.sheet = fold_points(&fold, &self.sheet);
+        self.folded += 1;
+        Some(fold)
+    }
+
+    /// The fold `fold_next` will make, if any are left.
+    pub fn next_fold(&self) -> Option<FoldAlong> {
+        self.folds.get(self.folded).copied()
+    }
+
+    /// The frames of the next fold, from the sheet as it is to the sheet
+    /// folded, or None once every fold is made. A sheet too big to show
+    /// gives a single frame noting its size.
+    pub fn fold_frames(&self, frames: usize, glyphs: Glyphs) -> Option<Vec<String>> {
+        let fold = self.next_fold()?;
+        let extent = sheet_extent(&self.sheet);
+        if extent.0 >= MAX_STEP_DRAWING.0 || extent.1 >= MAX_STEP_DRAWING.1 {
+            return Some(vec![step_drawing(&self.sheet, glyphs).join("\n") + "\n"]);
+        }
+        let frames = cmp::max(frames, 1);
+        Some(
+            (0..=frames)
+                .map(|k| fold_frame(&self.sheet, fold, k as f64 / frames as f64, extent, glyphs))
+                .collect(),
+        )
+    }
+
+    pub fn fold_all(&mut self) {
+        while self.fold_next().is_some() {}
+    }
+
+    pub fn points(&self) -> &Sheet {
+        &self.sheet
+    }
+
+    pub fn render(&self, glyphs: Glyphs) -> Vec<String> {
+        generate_drawing(&self.sheet, glyphs)
+    }
+
+    /// Renders the sheet, or notes its size if it is too big to show part
+    /// way through folding.
+    pub fn render_step(&self, glyphs: Glyphs) -> Vec<String> {
+        step_drawing(&self.sheet, glyphs)
+    }
+
+    /// The letters the dots spell, if they can be read.
+    pub fn code(&self) -> Option<String> {
+        read_letters(&self.sheet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_fold_points_for_y() {
+        let points: Vec<Point> = vec![
+            (6, 10),
+            (0, 14),
+            (9, 10),
+            (0, 3),
+            (10, 4),
+            (4, 11),
+            (6, 0),
+            (6, 12),
+            (4, 1),
+            (0, 13),
+            (10, 12),
+            (3, 4),
+            (3, 0),
+            (8, 4),
+            (1, 10),
+            (2, 14),
+            (8, 10),
+            (9, 0),
+        ];
+
+        let mut actual = fold_points(&FoldAlong::Y(7), &points.into_iter().collect())
+            .into_iter()
+            .collect::<Vec<_>>();
+        actual.sort();
+
+        assert_eq!(
+            actual,
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 3),
+                (1, 4),
+                (2, 0),
+                (3, 0),
+                (3, 4),
+                (4, 1),
+                (4, 3),
+                (6, 0),
+                (6, 2),
+                (6, 4),
+                (8, 4),
+                (9, 0),
+                (9, 4),
+                (10, 2),
+                (10, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_fold_points_for_x() {
+        let points: Vec<Point> = vec![
+            (0, 0),
+            (0, 1),
+            (0, 3),
+            (1, 4),
+            (2, 0),
+            (3, 0),
+            (3, 4),
+            (4, 1),
+            (4, 3),
+            (6, 0),
+            (6, 2),
+            (6, 4),
+            (8, 4),
+            (9, 0),
+            (9, 4),
+            (10, 2),
+            (10, 4),
+        ];
+
+        let expected: Vec<Point> = vec![
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 0),
+            (1, 4),
+            (2, 0),
+            (2, 4),
+            (3, 0),
+            (3, 4),
+            (4, 0),
+            (4, 1),
+            (4, 2),
+            (4, 3),
+            (4, 4),
+        ];
+
+        let mut actual = fold_points(&FoldAlong::X(5), &points.into_iter().collect())
+            .into_iter()
+            .collect::<Vec<_>>();
+        actual.sort();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn check_fold_frames() {
+        let mut origami = Origami::from_input(
+            ["0,0", "4,1", "", "fold along x=2"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        )
+        .unwrap();
+        let frames = origami.fold_frames(2, Glyphs::ASCII).unwrap();
+        let plain = |frame: &String| {
+            frame
+                .replace("\x1b[1;96m", "")
+                .replace("\x1b[1;92m", "")
+                .replace("\x1b[43m", "")
+                .replace("\x1b[0m", "")
+        };
+
+        assert_eq!(
+            frames.iter().map(plain).collect::<Vec<_>>(),
+            vec!["#....\n....#\n", "#....\n..#..\n", "#....\n#....\n"]
+        );
+        assert!(frames[1].contains("\x1b[1;96m#"));
+        assert!(frames[1].contains("\x1b[43m."));
+
+        origami.fold_all();
+        assert_eq!(origami.fold_frames(2, Glyphs::ASCII), None);
+    }
+
+    #[test]
+    fn check_generate_drawing() {
+        let points: Vec<Point> = vec![
+            (6, 10),
+            (0, 14),
+            (9, 10),
+            (0, 3),
+            (10, 4),
+            (4, 11),
+            (6, 0),
+            (6, 12),
+            (4, 1),
+            (0, 13),
+            (10, 12),
+            (3, 4),
+            (3, 0),
+            (8, 4),
+            (1, 10),
+            (2, 14),
+            (8, 10),
+            (9, 0),
+        ];
+
+        assert_eq!(
+            generate_drawing(&points.into_iter().collect(), Glyphs::default()),
+            vec![
+                "🦙🦙🦙🌍🦙🦙🌍🦙🦙🌍🦙",
+                "🦙🦙🦙🦙🌍🦙🦙🦙🦙🦙🦙",
+                "🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🌍🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🦙🦙🦙🌍🦙🦙🦙🦙🌍🦙🌍",
+                "🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🦙🌍🦙🦙🦙🦙🌍🦙🌍🌍🦙",
+                "🦙🦙🦙🦙🌍🦙🦙🦙🦙🦙🦙",
+                "🦙🦙🦙🦙🦙🦙🌍🦙🦙🦙🌍",
+                "🌍🦙🦙🦙🦙🦙🦙🦙🦙🦙🦙",
+                "🌍🦙🌍🦙🦙🦙🦙🦙🦙🦙🦙"
+            ]
+        );
+        let points: Vec<Point> = vec![
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 0),
+            (1, 4),
+            (2, 0),
+            (2, 4),
+            (3, 0),
+            (3, 4),
+            (4, 0),
+            (4, 1),
+            (4, 2),
+            (4, 3),
+            (4, 4),
+        ];
+
+        assert_eq!(
+            generate_drawing(&points.into_iter().collect(), Glyphs::default()),
+            vec![
+                "🌍🌍🌍🌍🌍",
+                "🌍🦙🦙🦙🌍",
+                "🌍🦙🦙🦙🌍",
+                "🌍🦙🦙🦙🌍",
+                "🌍🌍🌍🌍🌍"
+            ]
+        );
+    }
+
+    #[test]
+    fn check_read_letters() {
+        let points_of = |rows: &[&str]| {
+            rows.iter()
+                .zip(0..)
+                .flat_map(|(row, y)| {
+                    row.chars()
+                        .zip(0..)
+                        .filter(|(c, _)| *c == '#')
+                        .map(move |(_, x)| (x, y))
+                })
+                .collect::<Sheet>()
+        };
+        let points = points_of(&[
+            "#....###..####...##",
+            "#....#..#.#.......#",
+            "#....#..#.###.....#",
+            "#....###..#.......#",
+            "#....#.#..#....#..#",
+            "####.#..#.#.....##.",
+        ]);
+        assert_eq!(read_letters(&points), Some("LRFJ".to_owned()));
+
+        let square = points_of(&["#####", "#...#", "#...#", "#...#", "#####"]);
+        assert_eq!(read_letters(&square), None);
+        assert_eq!(
+            read_letters(&points_of(&["#", "#", "#", "#", "#", "#", "#"])),
+            None
+        );
+        assert_eq!(read_letters(&Sheet::new()), None);
+    }
+
+    #[test]
+    fn check_step_drawing() {
+        assert_eq!(
+            step_drawing(&Sheet::from([(0, 0), (1, 1)]), Glyphs::default()),
+            vec!["🌍🦙", "🦙🌍"]
+        );
+        assert_eq!(
+            step_drawing(&Sheet::from([(0, 0), (100, 3)]), Glyphs::default()),
+            vec!["The sheet is 101 by 4, too large to show"]
+        );
+        assert_eq!(
+            step_drawing(&Sheet::from([(0, 60)]), Glyphs::default()),
+            vec!["The sheet is 1 by 61, too large to show"]
+        );
+    }
+
+    #[test]
+    fn check_fold_merges_points() {
+        let sheet = Sheet::from([(0, 0), (4, 0), (1, 2), (3, 2)]);
+        assert_eq!(
+            fold_points(&FoldAlong::X(2), &sheet),
+            Sheet::from([(0, 0), (1, 2)])
+        );
+    }
+
+    #[test]
+    fn check_glyphs() {
+        let sheet = Sheet::from([(0, 0), (1, 1)]);
+        assert_eq!(generate_drawing(&sheet, Glyphs::ASCII), vec!["#.", ".#"]);
+        assert_eq!(
+            "o ".parse(),
+            Ok(Glyphs {
+                dot: 'o',
+                blank: ' '
+            })
+        );
+        assert_eq!(
+            generate_drawing(&sheet, "█ ".parse().unwrap()),
+            vec!["█ ", " █"]
+        );
+        assert!("#".parse::<Glyphs>().is_err());
+        assert!("#.x".parse::<Glyphs>().is_err());
+    }
+
+    #[test]
+    fn check_origami() {
+        let input =
+            "6,10 0,14 9,10 0,3 10,4 4,11 6,0 6,12 4,1 0,13 10,12 3,4 3,0 8,4 1,10 2,14 8,10 9,0"
+                .split(' ')
+                .chain(["", "fold along y=7", "fold along x=5"])
+                .map(str::to_owned)
+                .collect();
+        let mut origami = Origami::from_input(input).unwrap();
+        assert_eq!(origami.points().len(), 18);
+        assert_eq!(origami.fold_next(), Some(FoldAlong::Y(7)));
+        assert_eq!(origami.points().len(), 17);
+        origami.fold_all();
+        assert_eq!(origami.points().len(), 16);
+        assert_eq!(origami.fold_next(), None);
+        assert_eq!(
+            origami.render(Glyphs::ASCII),
+            vec!["#####", "#...#", "#...#", "#...#", "#####"]
+        );
+        assert_eq!(origami.code(), None);
+        assert!(Origami::from_input(vec!["1,x".to_owned()]).is_err());
+    }
+}
@@ -0,0 +1,820 @@
+use std::{collections::HashMap, fmt::Display, hash::Hash, rc::Rc, str::FromStr};
+
+/// A count of pairs or elements that reports overflow rather than wrapping.
+pub trait Count: Clone + Default + Ord + Display {
+    fn one() -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    fn difference(&self, smaller: &Self) -> Self;
+    fn half(&self) -> Self;
+}
+
+impl Count for u128 {
+    fn one() -> Self {
+        1
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u128::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        u128::checked_mul(*self, *other)
+    }
+
+    fn difference(&self, smaller: &Self) -> Self {
+        self - smaller
+    }
+
+    fn half(&self) -> Self {
+        self / 2
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Count for num_bigint::BigUint {
+    fn one() -> Self {
+        1u8.into()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn difference(&self, smaller: &Self) -> Self {
+        self - smaller
+    }
+
+    fn half(&self) -> Self {
+        self / 2u8
+    }
+}
+
+/// Adds `count` to the entry for `key`, failing if it overflows.
+fn add_count<K: Hash + Eq, C: Count>(
+    mapping: &mut HashMap<K, C>,
+    key: K,
+    count: &C,
+) -> Result<(), PolymerError> {
+    let entry = mapping.entry(key).or_default();
+    *entry = entry.checked_add(count).ok_or(PolymerError::Overflow)?;
+    Ok(())
+}
+
+fn count_pairs<C: Count>(polymer_template: &str) -> Result<HashMap<Pair, C>, PolymerError> {
+    let mut mapping = HashMap::with_capacity(polymer_template.len());
+
+    for (start, end) in polymer_template
+        .chars()
+        .zip(polymer_template.chars().skip(1))
+    {
+        add_count(&mut mapping, Pair { start, end }, &C::one())?;
+    }
+    Ok(mapping)
+}
+
+/// What to do with a pair that has no insertion rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingRules {
+    /// The pair carries over to the next step unchanged.
+    PassThrough,
+    /// The step fails with `PolymerError::NoRule`.
+    Reject,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PolymerError {
+    NoRule(Pair),
+    Overflow,
+    /// There are no pairs, so no elements to count.
+    Empty,
+}
+
+impl std::fmt::Display for PolymerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolymerError::NoRule(pair) => {
+                write!(f, "No mapping defined for pair: {}{}", pair.start, pair.end)
+            }
+            PolymerError::Overflow => write!(f, "The counts are too large to hold"),
+            PolymerError::Empty => write!(f, "No count"),
+        }
+    }
+}
+
+fn take_step<C: Count>(
+    pair_counts: &HashMap<Pair, C>,
+    pair_insertion_lookup: &HashMap<Pair, char>,
+    missing_rules: MissingRules,
+) -> Result<HashMap<Pair, C>, PolymerError> {
+    let mut mapping = HashMap::with_capacity(pair_counts.len());
+
+    for (pair, count) in pair_counts {
+        match pair_insertion_lookup.get(pair) {
+            Some(insert_char) => {
+                add_count(
+                    &mut mapping,
+                    Pair {
+                        start: pair.start,
+                        end: *insert_char,
+                    },
+                    count,
+                )?;
+                add_count(
+                    &mut mapping,
+                    Pair {
+                        start: *insert_char,
+                        end: pair.end,
+                    },
+                    count,
+                )?;
+            }
+            None => match missing_rules {
+                MissingRules::PassThrough => add_count(&mut mapping, *pair, count)?,
+                MissingRules::Reject => return Err(PolymerError::NoRule(*pair)),
+            },
+        }
+    }
+    Ok(mapping)
+}
+
+/// How `Polymer::step` moves the pair counts on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Engine {
+    /// Takes one insertion step at a time.
+    Iterative,
+    /// Raises the matrix of one step over every reachable pair to the
+    /// number of steps, taking time logarithmic in the steps.
+    Matrix,
+}
+
+/// `matrix[from][to]` is how many `to` pairs one `from` pair gives.
+type Matrix<C> = Vec<Vec<C>>;
+
+fn multiply<C: Count>(a: &Matrix<C>, b: &Matrix<C>) -> Result<Matrix<C>, PolymerError> {
+    let zero = C::default();
+    let mut product = vec![vec![zero.clone(); b.len()]; a.len()];
+    for (product_row, a_row) in product.iter_mut().zip(a) {
+        for (a_entry, b_row) in a_row.iter().zip(b) {
+            if *a_entry == zero {
+                continue;
+            }
+            for (cell, b_entry) in product_row.iter_mut().zip(b_row) {
+                let term = a_entry.checked_mul(b_entry).ok_or(PolymerError::Overflow)?;
+                *cell = cell.checked_add(&term).ok_or(PolymerError::Overflow)?;
+            }
+        }
+    }
+    Ok(product)
+}
+
+/// The same as `n` calls to `take_step`, by exponentiating the matrix of a
+/// single step.
+fn take_steps<C: Count>(
+    pair_counts: &HashMap<Pair, C>,
+    pair_insertion_lookup: &HashMap<Pair, char>,
+    missing_rules: MissingRules,
+    n: usize,
+) -> Result<HashMap<Pair, C>, PolymerError> {
+    // Every pair that can turn up, in the order a breadth first search
+    // reaches them, so a pair's depth is the first step it is present at.
+    let mut pairs = Vec::from_iter(pair_counts.keys().copied());
+    pairs.sort();
+    let mut index: HashMap<Pair, usize> = pairs.iter().zip(0..).map(|(p, i)| (*p, i)).collect();
+    let mut depths = vec![0; pairs.len()];
+    let mut successors = Vec::new();
+    let mut next = 0;
+    while next < pairs.len() {
+        let pair = pairs[next];
+        let produced = match pair_insertion_lookup.get(&pair) {
+            Some(insert) => vec![
+                Pair {
+                    start: pair.start,
+                    end: *insert,
+                },
+                Pair {
+                    start: *insert,
+                    end: pair.end,
+                },
+            ],
+            None => match missing_rules {
+                MissingRules::PassThrough => vec![pair],
+                MissingRules::Reject if depths[next] < n => return Err(PolymerError::NoRule(pair)),
+                MissingRules::Reject => vec![],
+            },
+        };
+        for to in &produced {
+            if !index.contains_key(to) {
+                index.insert(*to, pairs.len());
+                pairs.push(*to);
+                depths.push(depths[next] + 1);
+            }
+        }
+        successors.push(produced);
+        next += 1;
+    }
+
+    let zero = C::default();
+    let mut step = vec![vec![zero.clone(); pairs.len()]; pairs.len()];
+    for (row, produced) in step.iter_mut().zip(&successors) {
+        for to in produced {
+            let cell = &mut row[index[to]];
+            *cell = cell.checked_add(&C::one()).ok_or(PolymerError::Overflow)?;
+        }
+    }
+    let mut power: Matrix<C> = (0..pairs.len())
+        .map(|i| {
+            (0..pairs.len())
+                .map(|j| if i == j { C::one() } else { zero.clone() })
+                .collect()
+        })
+        .collect();
+    let mut remaining = n;
+    while remaining > 0 {
+        if remaining % 2 == 1 {
+            power = multiply(&power, &step)?;
+        }
+        remaining /= 2;
+        if remaining > 0 {
+            step = multiply(&step, &step)?;
+        }
+    }
+
+    let mut mapping = HashMap::with_capacity(pairs.len());
+    for (from, count) in pair_counts {
+        for (to, times) in pairs.iter().zip(&power[index[from]]) {
+            if *times != zero {
+                let produced = count.checked_mul(times).ok_or(PolymerError::Overflow)?;
+                add_count(&mut mapping, *to, &produced)?;
+            }
+        }
+    }
+    Ok(mapping)
+}
+
+/// The first and last elements of a template, which insertions never change.
+fn template_ends(polymer_template: &str) -> Option<(char, char)> {
+    Some((
+        polymer_template.chars().next()?,
+        polymer_template.chars().last()?,
+    ))
+}
+
+/// Every element is in two pairs, bar the first and last which are in one
+/// each, so counting those once more gives twice the count of each element.
+fn count_characters<C: Count>(
+    pair_counts: &HashMap<Pair, C>,
+    ends: Option<(char, char)>,
+) -> Result<HashMap<char, C>, PolymerError> {
+    let mut mapping = HashMap::with_capacity(pair_counts.len());
+
+    for (pair, count) in pair_counts.iter() {
+        add_count(&mut mapping, pair.start, count)?;
+        add_count(&mut mapping, pair.end, count)?;
+    }
+    if let Some((first, last)) = ends {
+        add_count(&mut mapping, first, &C::one())?;
+        add_count(&mut mapping, last, &C::one())?;
+    }
+    for val in mapping.values_mut() {
+        *val = val.half();
+    }
+    Ok(mapping)
+}
+
+/// The most and least common elements' counts.
+#[derive(Debug, PartialEq)]
+pub struct Score<C> {
+    pub max: C,
+    pub min: C,
+}
+
+impl<C: Count> Score<C> {
+    /// The most common element's count less the least common's.
+    pub fn value(&self) -> C {
+        self.max.difference(&self.min)
+    }
+}
+
+impl<C: Count> Display for Score<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "max:{}, min:{}, score:{}",
+            self.max,
+            self.min,
+            self.value()
+        )
+    }
+}
+
+/// The pair insertion rules, built once and shared by every polymer grown
+/// from them.
+#[derive(Debug, Clone, Default)]
+pub struct Rules(Rc<HashMap<Pair, char>>);
+
+impl Rules {
+    /// Reads `AB -> C` insertion rules, one to a line.
+    pub fn parse(lines: &[String]) -> Result<Self, String> {
+        let rules = lines
+            .iter()
+            .map(|s| s.parse())
+            .collect::<Result<Vec<PairInsertionRule>, _>>()?;
+        Ok(Rules(Rc::new(HashMap::from_iter(
+            rules.into_iter().map(|i| (i.pair, i.insert)),
+        ))))
+    }
+}
+
+/// Splits the input into its templates, each on a line of its own and
+/// separated by blank lines, and the rules that follow them.
+pub fn parse_batch(lines: &[String]) -> Result<(Vec<String>, Rules), String> {
+    let mut blocks = lines
+        .split(|line| line.trim().is_empty())
+        .filter(|block| !block.is_empty())
+        .collect::<Vec<_>>();
+    let rules = match blocks.len() {
+        0 => return Err("No polymer template".to_owned()),
+        1 => Rules::default(),
+        _ => Rules::parse(blocks.pop().unwrap_or_default())?,
+    };
+    let templates = blocks
+        .into_iter()
+        .map(|block| match block {
+            [template] => Ok(template.trim().to_owned()),
+            _ => Err(format!(
+                "Templates should be separated by blank lines: {:?}",
+                block
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((templates, rules))
+}
+
+/// A polymer held as the count of each pair of adjacent elements, along
+/// with the rules for growing it.
+#[derive(Debug, Clone)]
+pub struct Polymer<C = u128> {
+    pair_counts: HashMap<Pair, C>,
+    ends: Option<(char, char)>,
+    rules: Rules,
+    missing_rules: MissingRules,
+    engine: Engine,
+    steps: usize,
+}
+
+impl<C: Count> Polymer<C> {
+    /// Reads the template and the `AB -> C` insertion rules.
+    pub fn parse(template: &str, rules: &[String]) -> Result<Self, String> {
+        Polymer::new(template, &Rules::parse(rules)?).map_err(|e| e.to_string())
+    }
+
+    /// Starts a polymer from the template, sharing the rules.
+    pub fn new(template: &str, rules: &Rules) -> Result<Self, PolymerError> {
+        Ok(Polymer {
+            pair_counts: count_pairs(template)?,
+            ends: template_ends(template),
+            rules: rules.clone(),
+            missing_rules: MissingRules::PassThrough,
+            engine: Engine::Iterative,
+            steps: 0,
+        })
+    }
+
+    pub fn with_missing_rules(self, missing_rules: MissingRules) -> Self {
+        Polymer {
+            missing_rules,
+            ..self
+        }
+    }
+
+    pub fn with_engine(self, engine: Engine) -> Self {
+        Polymer { engine, ..self }
+    }
+
+    /// Takes `n` insertion steps.
+    pub fn step(&mut self, n: usize) -> Result<(), PolymerError> {
+        match self.engine {
+            Engine::Iterative => {
+                for _ in 0..n {
+                    self.pair_counts =
+                        take_step(&self.pair_counts, &self.rules.0, self.missing_rules)?;
+                    self.steps += 1;
+                }
+            }
+            Engine::Matrix => {
+                self.pair_counts =
+                    take_steps(&self.pair_counts, &self.rules.0, self.missing_rules, n)?;
+                self.steps += n;
+            }
+        }
+        Ok(())
+    }
+
+    /// How many steps have been taken.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn element_counts(&self) -> Result<HashMap<char, C>, PolymerError> {
+        count_characters(&self.pair_counts, self.ends)
+    }
+
+    /// Every element's count, most common first.
+    pub fn histogram(&self) -> Result<Vec<(char, C)>, PolymerError> {
+        let mut counts = Vec::from_iter(self.element_counts()?);
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(counts)
+    }
+
+    pub fn score(&self) -> Result<Score<C>, PolymerError> {
+        let counts = self.element_counts()?;
+        match (counts.values().max(), counts.values().min()) {
+            (Some(max), Some(min)) => Ok(Score {
+                max: max.clone(),
+                min: min.clone(),
+            }),
+            _ => Err(PolymerError::Empty),
+        }
+    }
+
+    /// How many of a pair or an element there are.
+    pub fn count_of(&self, tracked: &Tracked) -> Result<C, PolymerError> {
+        Ok(match tracked {
+            Tracked::Pair(pair) => self.pair_counts.get(pair).cloned(),
+            Tracked::Element(element) => self.element_counts()?.remove(element),
+        }
+        .unwrap_or_default())
+    }
+
+    /// Steps on to `until`, one step at a time, returning a CSV of the
+    /// tracked counts after every step.
+    pub fn track(&mut self, tracked: &[Tracked], until: usize) -> Result<String, PolymerError> {
+        let mut csv = std::iter::once("step".to_owned())
+            .chain(tracked.iter().map(Tracked::to_string))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push('\n');
+        loop {
+            let counts = tracked
+                .iter()
+                .map(|t| self.count_of(t).map(|count| count.to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            csv.push_str(&format!("{},{}\n", self.steps, counts.join(",")));
+            if self.steps >= until {
+                return Ok(csv);
+            }
+            self.step(1)?;
+        }
+    }
+
+    /// The score and element counts as a JSON object.
+    pub fn to_json(&self) -> Result<String, PolymerError> {
+        let score = self.score()?;
+        let counts = self
+            .histogram()?
+            .iter()
+            .map(|(ch, count)| match ch {
+                '"' | '\\' => format!("\"\\{}\": {}", ch, count),
+                ch => format!("\"{}\": {}", ch, count),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Ok(format!(
+            "{{\"step\": {}, \"max\": {}, \"min\": {}, \"score\": {}, \"counts\": {{{}}}}}",
+            self.steps,
+            score.max,
+            score.min,
+            score.value(),
+            counts
+        ))
+    }
+}
+
+/// A pair or an element whose count is followed step by step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tracked {
+    Pair(Pair),
+    Element(char),
+}
+
+impl FromStr for Tracked {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.chars().collect::<Vec<char>>()[..] {
+            [start, end] => Ok(Tracked::Pair(Pair { start, end })),
+            [element] => Ok(Tracked::Element(element)),
+            _ => Err(format!(
+                "Can't track {:?}, expected a pair like NC or an element like B",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Tracked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tracked::Pair(pair) => write!(f, "{}{}", pair.start, pair.end),
+            Tracked::Element(element) => write!(f, "{}", element),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pair {
+    pub start: char,
+    pub end: char,
+}
+
+impl FromStr for Pair {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.chars().collect::<Vec<char>>()[..] {
+            [start, end] => Ok(Pair { start, end }),
+            _ => Err(format!("Bad pair insertion rules: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PairInsertionRule {
+    pair: Pair,
+    insert: char,
+}
+
+impl FromStr for PairInsertionRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let [elements, element] = s.split(" -> ").collect::<Vec<&str>>()[..] {
+            let pair: Pair = elements.parse()?;
+            if let [insert] = element.chars().collect::<Vec<char>>()[..] {
+                return Ok(PairInsertionRule { pair, insert });
+            }
+        }
+        Err(format!("Bad pair insertion rules: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pair_insertion_rules() -> Vec<PairInsertionRule> {
+        vec![
+            "CH -> B".parse().unwrap(),
+            "HH -> N".parse().unwrap(),
+            "CB -> H".parse().unwrap(),
+            "NH -> C".parse().unwrap(),
+            "HB -> C".parse().unwrap(),
+            "HC -> B".parse().unwrap(),
+            "HN -> C".parse().unwrap(),
+            "NN -> C".parse().unwrap(),
+            "BH -> H".parse().unwrap(),
+            "NC -> B".parse().unwrap(),
+            "NB -> B".parse().unwrap(),
+            "BN -> B".parse().unwrap(),
+            "BB -> N".parse().unwrap(),
+            "BC -> B".parse().unwrap(),
+            "CC -> N".parse().unwrap(),
+            "CN -> C".parse().unwrap(),
+        ]
+    }
+
+    #[test]
+    fn check_count_pairs() {
+        let polymer_template = "NNCB".to_string();
+
+        let pair_counters = count_pairs::<u128>(&polymer_template).unwrap();
+        let mut entries = Vec::from_iter(pair_counters);
+        entries.sort();
+
+        assert_eq!(
+            entries,
+            vec![
+                ("CB".parse().unwrap(), 1),
+                ("NC".parse().unwrap(), 1),
+                ("NN".parse().unwrap(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn check_take_step() {
+        let polymer_template = "NNCB".to_string();
+        let rules = sample_pair_insertion_rules();
+        let pair_insertion_lookup: HashMap<Pair, char> =
+            HashMap::from_iter(rules.into_iter().map(|i| (i.pair, i.insert)));
+        let initial = count_pairs::<u128>(&polymer_template).unwrap();
+
+        let step = take_step(&initial, &pair_insertion_lookup, MissingRules::Reject).unwrap();
+        let mut entries = Vec::from_iter(step.iter());
+        entries.sort();
+
+        assert_eq!(
+            entries,
+            vec![
+                (&"BC".parse().unwrap(), &1),
+                (&"CH".parse().unwrap(), &1),
+                (&"CN".parse().unwrap(), &1),
+                (&"HB".parse().unwrap(), &1),
+                (&"NB".parse().unwrap(), &1),
+                (&"NC".parse().unwrap(), &1),
+            ]
+        );
+        let step = take_step(&step, &pair_insertion_lookup, MissingRules::Reject).unwrap();
+        let mut entries = Vec::from_iter(step.iter());
+        entries.sort();
+
+        assert_eq!(
+            entries,
+            vec![
+                (&"BB".parse().unwrap(), &2),
+                (&"BC".parse().unwrap(), &2),
+                (&"BH".parse().unwrap(), &1),
+                (&"CB".parse().unwrap(), &2),
+                (&"CC".parse().unwrap(), &1),
+                (&"CN".parse().unwrap(), &1),
+                (&"HC".parse().unwrap(), &1),
+                (&"NB".parse().unwrap(), &2),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_take_step_without_rule() {
+        let pair_insertion_lookup: HashMap<Pair, char> =
+            HashMap::from_iter([("NN".parse().unwrap(), 'C')]);
+        let initial = count_pairs::<u128>("NNCB").unwrap();
+
+        let step = take_step(&initial, &pair_insertion_lookup, MissingRules::PassThrough).unwrap();
+        let mut entries = Vec::from_iter(step);
+        entries.sort();
+
+        assert_eq!(
+            entries,
+            vec![
+                ("CB".parse().unwrap(), 1),
+                ("CN".parse().unwrap(), 1),
+                ("NC".parse().unwrap(), 2),
+            ]
+        );
+        assert!(matches!(
+            take_step(&initial, &pair_insertion_lookup, MissingRules::Reject),
+            Err(PolymerError::NoRule(Pair {
+                start: 'N',
+                end: 'C'
+            })) | Err(PolymerError::NoRule(Pair {
+                start: 'C',
+                end: 'B'
+            }))
+        ));
+    }
+
+    #[test]
+    fn check_take_step_overflow() {
+        let pair_insertion_lookup: HashMap<Pair, char> =
+            HashMap::from_iter([("NN".parse().unwrap(), 'N')]);
+        let initial = HashMap::from_iter([("NN".parse().unwrap(), u128::MAX / 2 + 1)]);
+
+        assert_eq!(
+            take_step(&initial, &pair_insertion_lookup, MissingRules::Reject),
+            Err(PolymerError::Overflow)
+        );
+    }
+
+    #[test]
+    fn check_count_characters() {
+        let mut entries = Vec::from_iter(
+            count_characters(&count_pairs::<u128>("NNCB").unwrap(), template_ends("NNCB")).unwrap(),
+        );
+        entries.sort();
+
+        assert_eq!(entries, vec![('B', 1), ('C', 1), ('N', 2)]);
+
+        let polymer = "NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB";
+        let mut entries = Vec::from_iter(
+            count_characters(
+                &count_pairs::<u128>(polymer).unwrap(),
+                template_ends(polymer),
+            )
+            .unwrap(),
+        );
+        entries.sort();
+
+        assert_eq!(entries, vec![('B', 23), ('C', 10), ('H', 5), ('N', 11)]);
+
+        let polymer = Polymer::<u128>::new("NBN", &Rules::default()).unwrap();
+        assert_eq!(polymer.histogram(), Ok(vec![('N', 2), ('B', 1)]));
+        assert_eq!(polymer.score(), Ok(Score { max: 2, min: 1 }));
+        let polymer = Polymer::<u128>::new("N", &Rules::default()).unwrap();
+        assert_eq!(polymer.histogram(), Ok(vec![('N', 1)]));
+    }
+
+    #[test]
+    fn check_take_steps_matches_take_step() {
+        let rules = sample_pair_insertion_rules();
+        let all_rules: HashMap<Pair, char> =
+            HashMap::from_iter(rules.into_iter().map(|i| (i.pair, i.insert)));
+        let mut some_rules = all_rules.clone();
+        some_rules.remove(&"CB".parse().unwrap());
+        some_rules.remove(&"NN".parse().unwrap());
+
+        for lookup in [&all_rules, &some_rules] {
+            let initial = count_pairs::<u128>("NNCB").unwrap();
+            let mut iterated = initial.clone();
+            for n in 0..=20 {
+                assert_eq!(
+                    take_steps(&initial, lookup, MissingRules::PassThrough, n),
+                    Ok(iterated.clone())
+                );
+                iterated = take_step(&iterated, lookup, MissingRules::PassThrough).unwrap();
+            }
+        }
+
+        let initial = count_pairs::<u128>("NCNBCHB").unwrap();
+        assert_eq!(
+            take_steps(&initial, &some_rules, MissingRules::Reject, 1),
+            Ok(take_step(&initial, &some_rules, MissingRules::Reject).unwrap())
+        );
+        assert_eq!(
+            take_steps(&initial, &some_rules, MissingRules::Reject, 2),
+            Err(PolymerError::NoRule("CB".parse().unwrap()))
+        );
+        assert_eq!(
+            take_steps(&initial, &all_rules, MissingRules::Reject, 200),
+            Err(PolymerError::Overflow)
+        );
+    }
+
+    #[test]
+    fn check_parse_batch() {
+        let lines = ["NNCB", "", "  ", "CH", "", "CH -> B", "HH -> N"].map(String::from);
+        let (templates, rules) = parse_batch(&lines).unwrap();
+
+        assert_eq!(templates, vec!["NNCB", "CH"]);
+        assert_eq!(rules.0.len(), 2);
+
+        let mut first = Polymer::<u128>::new(&templates[0], &rules).unwrap();
+        let second = Polymer::<u128>::new(&templates[1], &rules).unwrap();
+        assert!(Rc::ptr_eq(&first.rules.0, &second.rules.0));
+        first.step(1).unwrap();
+        assert_eq!(
+            first.histogram().unwrap(),
+            vec![('N', 2), ('B', 1), ('C', 1)]
+        );
+
+        assert_eq!(
+            parse_batch(&["NNCB".to_owned()]).unwrap().0,
+            vec!["NNCB".to_owned()]
+        );
+        assert!(parse_batch(&["NNCB", "CH", "", "CH -> B"].map(String::from)).is_err());
+        assert!(parse_batch(&[]).is_err());
+    }
+
+    #[test]
+    fn check_polymer() {
+        let rules = [
+            "CH -> B", "HH -> N", "CB -> H", "NH -> C", "HB -> C", "HC -> B", "HN -> C", "NN -> C",
+            "BH -> H", "NC -> B", "NB -> B", "BN -> B", "BB -> N", "BC -> B", "CC -> N", "CN -> C",
+        ]
+        .map(String::from);
+        let mut polymer = Polymer::<u128>::parse("NNCB", &rules).unwrap();
+
+        polymer.step(10).unwrap();
+        assert_eq!(polymer.steps(), 10);
+        assert_eq!(
+            polymer.histogram().unwrap(),
+            vec![('B', 1749), ('N', 865), ('C', 298), ('H', 161)]
+        );
+        assert_eq!(
+            polymer.to_json().unwrap(),
+            "{\"step\": 10, \"max\": 1749, \"min\": 161, \"score\": 1588, \"counts\": {\"B\": 1749, \"N\": 865, \"C\": 298, \"H\": 161}}"
+        );
+
+        let tracked = ["NC".parse().unwrap(), "B".parse().unwrap()];
+        assert_eq!(
+            polymer.clone().track(&tracked, 12).unwrap(),
+            "step,NC,B\n10,42,1749\n11,87,3539\n12,134,7256\n"
+        );
+        assert!("NCB".parse::<Tracked>().is_err());
+
+        polymer.step(30).unwrap();
+        assert_eq!(polymer.score().unwrap().value(), 2188189693529);
+        assert_eq!(
+            Polymer::<u128>::parse("N", &rules).unwrap().score(),
+            Ok(Score { max: 1, min: 1 })
+        );
+        assert_eq!(
+            Polymer::<u128>::parse("", &rules).unwrap().score(),
+            Err(PolymerError::Empty)
+        );
+    }
+}
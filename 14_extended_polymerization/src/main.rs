@@ -1,73 +1,78 @@
-use std::{
-    collections::HashMap,
-    hash::Hash,
-    io::{BufRead, BufReader},
-    ops::{Add, Div},
-    str::FromStr,
-};
-
-fn count_pairs(polymer_template: &String) -> HashMap<Pair, u64> {
-    let mut mapping = HashMap::with_capacity(polymer_template.len());
-
-    for (start, end) in polymer_template
-        .chars()
-        .zip(polymer_template.chars().skip(1))
-    {
-        *mapping.entry(Pair { start, end }).or_insert(0) += 1;
-    }
-    mapping
+use extended_polymerization::{parse_batch, Count, Engine, MissingRules, Polymer, Rules, Tracked};
+use std::io::{BufRead, BufReader};
+
+/// What to report about each polymer and how to grow it.
+struct Report {
+    steps: Vec<usize>,
+    tracked: Option<Vec<Tracked>>,
+    show_histogram: bool,
+    json: bool,
+    missing_rules: MissingRules,
+    engine: Engine,
 }
 
-fn take_step(
-    pair_counts: &HashMap<Pair, u64>,
-    pair_insertion_lookup: &HashMap<Pair, char>,
-) -> HashMap<Pair, u64> {
-    let mut mapping = HashMap::with_capacity(pair_counts.len());
-
-    for (pair, count) in pair_counts {
-        match pair_insertion_lookup.get(pair) {
-            Some(insert_char) => {
-                *mapping
-                    .entry(Pair {
-                        start: pair.start,
-                        end: insert_char.clone(),
-                    })
-                    .or_insert(0) += count;
-                *mapping
-                    .entry(Pair {
-                        start: insert_char.clone(),
-                        end: pair.end,
-                    })
-                    .or_insert(0) += count;
-            }
-            None => {
-                panic!("No mapping defined for pair: {:?}", pair);
+/// Steps the polymer up to the last of the steps, reporting on it after
+/// each of them.
+fn run<C: Count>(mut polymer: Polymer<C>, report: &Report) -> Result<String, String> {
+    if let Some(tracked) = &report.tracked {
+        let until = report.steps.last().copied().unwrap_or(0);
+        return polymer
+            .track(tracked, until)
+            .map_err(|e| format!("Step {}: {}", polymer.steps() + 1, e));
+    }
+    let mut output = String::new();
+    let mut json_steps = Vec::new();
+    for &step in &report.steps {
+        polymer
+            .step(step - polymer.steps())
+            .map_err(|e| format!("Step {}: {}", polymer.steps() + 1, e))?;
+        let error = |e| format!("Step {}: {}", step, e);
+        if report.json {
+            json_steps.push(polymer.to_json().map_err(error)?);
+            continue;
+        }
+        output.push_str(&format!(
+            "After {} steps. {}\n",
+            step,
+            polymer.score().map_err(error)?
+        ));
+        if report.show_histogram {
+            for (ch, count) in polymer.histogram().map_err(error)? {
+                output.push_str(&format!("  {}: {}\n", ch, count));
             }
         }
     }
-    mapping
+    if report.json {
+        output = format!("[{}]", json_steps.join(",\n "));
+    }
+    Ok(output)
 }
 
-fn count_characters(pair_counts: &HashMap<Pair, u64>) -> HashMap<char, u64> {
-    let mut mapping = HashMap::with_capacity(pair_counts.len());
-
-    for (pair, count) in pair_counts.into_iter() {
-        *mapping.entry(pair.start).or_insert(0) += count;
-        *mapping.entry(pair.end).or_insert(0) += count;
+/// Reports on every template grown with the shared rules.
+fn run_batch<C: Count>(templates: &[String], rules: &Rules, report: &Report) -> Result<(), String> {
+    let batch = templates.len() > 1;
+    let mut outputs = Vec::new();
+    for (template, number) in templates.iter().zip(1..) {
+        let polymer = Polymer::<C>::new(template, rules)
+            .map_err(|e| e.to_string())?
+            .with_missing_rules(report.missing_rules)
+            .with_engine(report.engine);
+        let output = run(polymer, report).map_err(|e| match batch {
+            true => format!("Template {}: {}", number, e),
+            false => e,
+        })?;
+        outputs.push(match (batch, report.json) {
+            (true, true) => format!("{{\"template\": {:?}, \"steps\": {}}}", template, output),
+            (true, false) => format!("Template {}: {}\n{}", number, template, output),
+            (false, _) => output,
+        });
     }
-    for val in mapping.values_mut() {
-        *val = val.add(1).div(2);
+    match (batch, report.json) {
+        (true, true) => println!("[{}]", outputs.join(",\n")),
+        (false, true) => println!("{}", outputs.join("")),
+        (_, false) => print!("{}", outputs.join("")),
     }
-    mapping
-}
-
-fn score_count(char_count: &HashMap<char, u64>) -> Option<(u64, u64)> {
-    char_count.values().max().and_then(|max| {
-        char_count
-            .values()
-            .min()
-            .map(|min| (max.to_owned(), min.to_owned()))
-    })
+    Ok(())
 }
 
 fn main() -> Result<(), String> {
@@ -76,178 +81,82 @@ fn main() -> Result<(), String> {
         .collect::<Result<Vec<String>, _>>()
         .map_err(|e| e.to_string())?;
 
-    let mut iter = lines.iter();
-    let polymer_template = iter.next().ok_or("No polymer template")?;
-    let pair_insertion_rules = iter
-        .skip(1)
-        .map(|s| s.parse())
-        .collect::<Result<Vec<PairInsertionRule>, _>>()?;
-
-    let pair_insertion_lookup: HashMap<Pair, char> =
-        HashMap::from_iter(pair_insertion_rules.into_iter().map(|i| (i.pair, i.insert)));
-
-    let after_ten_steps = (0..10)
-        .into_iter()
-        .fold(count_pairs(polymer_template), |x, _| {
-            take_step(&x, &pair_insertion_lookup)
-        });
-
-    let (max, min) = score_count(&count_characters(&after_ten_steps)).ok_or("No count")?;
-    println!(
-        "After ten steps. max:{}, min:{}, score:{}",
-        max,
-        min,
-        max - min
-    );
-
-    let after_fourty_steps = (10..40).into_iter().fold(after_ten_steps, |x, _| {
-        take_step(&x, &pair_insertion_lookup)
-    });
-    let (max, min) = score_count(&count_characters(&after_fourty_steps)).ok_or("No count")?;
-    println!(
-        "After ten steps. max:{}, min:{}, score:{}",
-        max,
-        min,
-        max - min
-    );
-
-    Ok(())
-}
-
-#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct Pair {
-    start: char,
-    end: char,
-}
-
-impl FromStr for Pair {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.chars().collect::<Vec<char>>()[..] {
-            [start, end] => Ok(Pair { start, end }),
-            _ => Err(format!("Bad pair insertion rules: {}", s)),
-        }
+    let args: Vec<String> = std::env::args().collect();
+    let mut steps = args
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--steps")
+        .map(|(i, _)| {
+            args.get(i + 1)
+                .and_then(|n| n.parse::<usize>().ok())
+                .ok_or("--steps should be followed by a number of steps")
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    if steps.is_empty() {
+        steps = vec![10, 40];
     }
-}
-
-#[derive(Debug)]
-struct PairInsertionRule {
-    pair: Pair,
-    insert: char,
-}
-
-impl FromStr for PairInsertionRule {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let [elements, element] = s.split(" -> ").collect::<Vec<&str>>()[..] {
-            let pair: Pair = elements.parse()?;
-            if let [insert] = element.chars().collect::<Vec<char>>()[..] {
-                return Ok(PairInsertionRule { pair, insert });
+    steps.sort_unstable();
+    steps.dedup();
+    let missing_rules = if args.iter().any(|a| a == "--strict") {
+        MissingRules::Reject
+    } else {
+        MissingRules::PassThrough
+    };
+    let show_histogram = args.iter().any(|a| a == "--histogram");
+    let json = match args.iter().position(|a| a == "--format") {
+        Some(i) => match args.get(i + 1).map(String::as_str) {
+            Some("json") => true,
+            Some("text") => false,
+            format => {
+                return Err(format!(
+                    "Unknown format {:?}, expected json or text",
+                    format
+                ))
             }
-        }
-        Err(format!("Bad pair insertion rules: {}", s))
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn sample_pair_insertion_rules() -> Vec<PairInsertionRule> {
-        vec![
-            "CH -> B".parse().unwrap(),
-            "HH -> N".parse().unwrap(),
-            "CB -> H".parse().unwrap(),
-            "NH -> C".parse().unwrap(),
-            "HB -> C".parse().unwrap(),
-            "HC -> B".parse().unwrap(),
-            "HN -> C".parse().unwrap(),
-            "NN -> C".parse().unwrap(),
-            "BH -> H".parse().unwrap(),
-            "NC -> B".parse().unwrap(),
-            "NB -> B".parse().unwrap(),
-            "BN -> B".parse().unwrap(),
-            "BB -> N".parse().unwrap(),
-            "BC -> B".parse().unwrap(),
-            "CC -> N".parse().unwrap(),
-            "CN -> C".parse().unwrap(),
-        ]
-    }
-
-    #[test]
-    fn check_count_pairs() {
-        let polymer_template = "NNCB".to_string();
-
-        let pair_counters = count_pairs(&polymer_template);
-        let mut entries = Vec::from_iter(pair_counters);
-        entries.sort();
-
-        assert_eq!(
-            entries,
-            vec![
-                ("CB".parse().unwrap(), 1),
-                ("NC".parse().unwrap(), 1),
-                ("NN".parse().unwrap(), 1)
-            ]
-        );
-    }
-
-    #[test]
-    fn check_take_step() {
-        let polymer_template = "NNCB".to_string();
-        let rules = sample_pair_insertion_rules();
-        let pair_insertion_lookup: HashMap<Pair, char> =
-            HashMap::from_iter(rules.into_iter().map(|i| (i.pair, i.insert)));
-        let initial = count_pairs(&polymer_template);
-
-        let step = take_step(&initial, &pair_insertion_lookup);
-        let mut entries = Vec::from_iter(step.iter());
-        entries.sort();
-
-        assert_eq!(
-            entries,
-            vec![
-                (&"BC".parse().unwrap(), &1),
-                (&"CH".parse().unwrap(), &1),
-                (&"CN".parse().unwrap(), &1),
-                (&"HB".parse().unwrap(), &1),
-                (&"NB".parse().unwrap(), &1),
-                (&"NC".parse().unwrap(), &1),
-            ]
-        );
-        let step = take_step(&step, &pair_insertion_lookup);
-        let mut entries = Vec::from_iter(step.iter());
-        entries.sort();
-
-        assert_eq!(
-            entries,
-            vec![
-                (&"BB".parse().unwrap(), &2),
-                (&"BC".parse().unwrap(), &2),
-                (&"BH".parse().unwrap(), &1),
-                (&"CB".parse().unwrap(), &2),
-                (&"CC".parse().unwrap(), &1),
-                (&"CN".parse().unwrap(), &1),
-                (&"HC".parse().unwrap(), &1),
-                (&"NB".parse().unwrap(), &2),
-            ]
-        );
-    }
-
-    #[test]
-    fn check_count_characters() {
-        let mut entries = Vec::from_iter(count_characters(&count_pairs(&"NNCB".to_string())));
-        entries.sort();
-
-        assert_eq!(entries, vec![('B', 1), ('C', 1), ('N', 2)]);
-
-        let mut entries = Vec::from_iter(count_characters(&count_pairs(
-            &"NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB".to_string(),
-        )));
-        entries.sort();
-
-        assert_eq!(entries, vec![('B', 23), ('C', 10), ('H', 5), ('N', 11)]);
+        },
+        None => false,
+    };
+
+    let engine = match args.iter().position(|a| a == "--engine") {
+        Some(i) => match args.get(i + 1).map(String::as_str) {
+            Some("iterative") => Engine::Iterative,
+            Some("matrix") => Engine::Matrix,
+            engine => {
+                return Err(format!(
+                    "Unknown engine {:?}, expected iterative or matrix",
+                    engine
+                ))
+            }
+        },
+        None => Engine::Iterative,
+    };
+
+    let tracked = match args.iter().position(|a| a == "--track") {
+        Some(i) => Some(
+            args.get(i + 1)
+                .ok_or("--track should be followed by pairs and elements, like NC,B")?
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<Tracked>, _>>()?,
+        ),
+        None => None,
+    };
+
+    let report = Report {
+        steps,
+        tracked,
+        show_histogram,
+        json,
+        missing_rules,
+        engine,
+    };
+    let (templates, rules) = parse_batch(&lines)?;
+
+    if args.iter().any(|a| a == "--bigint") {
+        #[cfg(feature = "bigint")]
+        return run_batch::<num_bigint::BigUint>(&templates, &rules, &report);
+        #[cfg(not(feature = "bigint"))]
+        return Err("--bigint needs a build with the bigint feature".to_owned());
     }
+    run_batch::<u128>(&templates, &rules, &report)
 }
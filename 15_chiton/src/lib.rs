@@ -0,0 +1,949 @@
+use pathfinding::prelude::{astar, dijkstra};
+use std::{
+    cmp::{self, Reverse},
+    collections::{BinaryHeap, HashSet},
+};
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Pos(pub usize, pub usize);
+
+/// The cost of entering a position on the map tiled any number of times,
+/// where each tile away from the top left adds one to the risk, wrapping
+/// from 9 back round to 1.
+pub fn cost_large_map_edge(p: Pos, lines: &[Vec<usize>]) -> (Pos, usize) {
+    let quot_x = p.0 / lines.len();
+    let quot_y = p.1 / lines[0].len();
+    let rem_x = p.0 % lines.len();
+    let rem_y = p.1 % lines[0].len();
+
+    let cost = (lines[rem_x][rem_y] + quot_x + quot_y - 1) % 9 + 1;
+    (p, cost)
+}
+
+pub fn cost_edge(p: Pos, lines: &[Vec<usize>]) -> (Pos, usize) {
+    let cost = lines[p.0][p.1];
+    (p, cost)
+}
+
+pub fn successors<FN>(p: &Pos, edge: &Pos, cost: FN) -> Vec<(Pos, usize)>
+where
+    FN: Fn(Pos) -> (Pos, usize),
+{
+    let mut successors = Vec::default();
+    if p.0 < edge.0 {
+        successors.push(Pos(p.0 + 1, p.1))
+    }
+    if p.1 < edge.1 {
+        successors.push(Pos(p.0, p.1 + 1))
+    }
+    if p.0 > 0 {
+        successors.push(Pos(p.0 - 1, p.1))
+    }
+    if p.1 > 0 {
+        successors.push(Pos(p.0, p.1 - 1))
+    }
+    successors.into_iter().map(cost).collect()
+}
+
+/// The cost of a diagonal step, from the risk of the position it enters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagonalCost {
+    Same,
+    Double,
+    Extra(usize),
+}
+
+impl DiagonalCost {
+    pub fn apply(self, risk: usize) -> usize {
+        match self {
+            DiagonalCost::Same => risk,
+            DiagonalCost::Double => risk * 2,
+            DiagonalCost::Extra(extra) => risk + extra,
+        }
+    }
+}
+
+impl std::str::FromStr for DiagonalCost {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "same" => Ok(DiagonalCost::Same),
+            "double" => Ok(DiagonalCost::Double),
+            _ => s
+                .strip_prefix('+')
+                .and_then(|extra| extra.parse().ok())
+                .map(DiagonalCost::Extra)
+                .ok_or(format!(
+                    "Unknown diagonal cost {:?}, expected same, double or +N",
+                    s
+                )),
+        }
+    }
+}
+
+/// Which neighbours a step can move to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Moves {
+    Orthogonal,
+    Diagonal(DiagonalCost),
+}
+
+/// The four orthogonal successors followed by the diagonal ones, costed by
+/// the rule.
+pub fn diagonal_successors<FN>(
+    p: &Pos,
+    edge: &Pos,
+    cost: FN,
+    rule: DiagonalCost,
+) -> Vec<(Pos, usize)>
+where
+    FN: Fn(Pos) -> (Pos, usize),
+{
+    let mut diagonals = Vec::default();
+    for (down, right) in [(true, true), (true, false), (false, true), (false, false)] {
+        let row = if down {
+            Some(p.0 + 1).filter(|row| *row <= edge.0)
+        } else {
+            p.0.checked_sub(1)
+        };
+        let col = if right {
+            Some(p.1 + 1).filter(|col| *col <= edge.1)
+        } else {
+            p.1.checked_sub(1)
+        };
+        if let (Some(row), Some(col)) = (row, col) {
+            diagonals.push(Pos(row, col));
+        }
+    }
+    let mut successors = successors(p, edge, &cost);
+    successors.extend(diagonals.into_iter().map(|p| {
+        let (p, risk) = cost(p);
+        (p, rule.apply(risk))
+    }));
+    successors
+}
+
+/// How the lowest risk path is searched for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Search {
+    Dijkstra,
+    /// A* with the Manhattan distance to the goal as the heuristic, or the
+    /// Chebyshev distance when moving diagonally, which never overestimates
+    /// as every step costs at least 1.
+    AStar,
+}
+
+fn manhattan(p: &Pos, goal: &Pos) -> usize {
+    p.0.abs_diff(goal.0) + p.1.abs_diff(goal.1)
+}
+
+fn chebyshev(p: &Pos, goal: &Pos) -> usize {
+    cmp::max(p.0.abs_diff(goal.0), p.1.abs_diff(goal.1))
+}
+
+/// The lowest risk path from the top left to the goal, along with how many
+/// nodes were expanded to find it.
+pub fn find_path<FN>(
+    goal: &Pos,
+    cost: FN,
+    search: Search,
+    moves: Moves,
+) -> (Option<(Vec<Pos>, usize)>, usize)
+where
+    FN: Fn(Pos) -> (Pos, usize),
+{
+    let mut expanded = 0;
+    let mut expand = |p: &Pos| {
+        expanded += 1;
+        match moves {
+            Moves::Orthogonal => successors(p, goal, &cost),
+            Moves::Diagonal(rule) => diagonal_successors(p, goal, &cost, rule),
+        }
+    };
+    let heuristic = match moves {
+        Moves::Orthogonal => manhattan,
+        Moves::Diagonal(_) => chebyshev,
+    };
+    let path = match search {
+        Search::Dijkstra => dijkstra(&Pos(0, 0), &mut expand, |p| p == goal),
+        Search::AStar => astar(
+            &Pos(0, 0),
+            &mut expand,
+            |p| heuristic(p, goal),
+            |p| p == goal,
+        ),
+    };
+    (path, expanded)
+}
+
+/// The risks of a map in one flat array, row by row, so the search can use
+/// `u32` indices as its nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RiskGrid {
+    risks: Vec<u8>,
+    rows: usize,
+    cols: usize,
+}
+
+/// A move to a neighbour, with the change in flat index worked out once for
+/// the grid's width.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Offset {
+    rows: isize,
+    cols: isize,
+    index: isize,
+    diagonal: bool,
+}
+
+impl RiskGrid {
+    /// Reads a map of risk digits, one row to a line.
+    pub fn parse(lines: &[String]) -> Result<Self, String> {
+        let rows = lines
+            .iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|c| match c.to_digit(10) {
+                        Some(risk @ 1..=9) => Ok(risk as usize),
+                        _ => Err(format!("Bad risk level {:?} in {:?}", c, line)),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<usize>>, _>>()?;
+        RiskGrid::from_rows(&rows)
+    }
+
+    /// Packs rows of risk levels, which must be the same length and each
+    /// from 1 to 9.
+    pub fn from_rows(lines: &[Vec<usize>]) -> Result<Self, String> {
+        let cols = lines.first().map_or(0, Vec::len);
+        if cols == 0 {
+            return Err("No risk levels".to_owned());
+        }
+        if lines.iter().any(|row| row.len() != cols) {
+            return Err("The rows of risk levels differ in length".to_owned());
+        }
+        for (row, r) in lines.iter().zip(0..) {
+            if let Some((risk, c)) = row
+                .iter()
+                .zip(0..)
+                .find(|(risk, _)| !(1..=9).contains(*risk))
+            {
+                return Err(format!("Bad risk level {} at row {} col {}", risk, r, c));
+            }
+        }
+        if u32::try_from(lines.len() * cols).is_err() {
+            return Err(format!(
+                "A {} by {} map is too big to search",
+                lines.len(),
+                cols
+            ));
+        }
+        Ok(RiskGrid {
+            risks: lines.iter().flatten().map(|risk| *risk as u8).collect(),
+            rows: lines.len(),
+            cols,
+        })
+    }
+
+    /// The risks row by row, as the position based search takes them.
+    pub fn to_rows(&self) -> Vec<Vec<usize>> {
+        self.risks
+            .chunks(self.cols)
+            .map(|row| row.iter().map(|risk| usize::from(*risk)).collect())
+            .collect()
+    }
+
+    /// The map repeated `factor` times each way, as `cost_large_map_edge`
+    /// costs it.
+    pub fn tiled(&self, factor: usize) -> Result<Self, String> {
+        let (rows, cols) = (self.rows * factor, self.cols * factor);
+        if u32::try_from(rows * cols).is_err() {
+            return Err(format!("A {} by {} map is too big to search", rows, cols));
+        }
+        let risks = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| {
+                let risk = self.risk(&Pos(row % self.rows, col % self.cols));
+                ((risk + row / self.rows + col / self.cols - 1) % 9 + 1) as u8
+            })
+            .collect();
+        Ok(RiskGrid { risks, rows, cols })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn risk(&self, p: &Pos) -> usize {
+        usize::from(self.risks[p.0 * self.cols + p.1])
+    }
+
+    pub fn pos(&self, index: u32) -> Pos {
+        let index = index as usize;
+        Pos(index / self.cols, index % self.cols)
+    }
+
+    /// The moves in the same order as `successors` and
+    /// `diagonal_successors` make them.
+    pub fn offsets(&self, moves: Moves) -> Vec<Offset> {
+        let mut steps = vec![(1, 0), (0, 1), (-1, 0), (0, -1)];
+        if let Moves::Diagonal(_) = moves {
+            steps.extend([(1, 1), (1, -1), (-1, 1), (-1, -1)]);
+        }
+        let width = self.cols as isize;
+        steps
+            .into_iter()
+            .map(|(rows, cols)| Offset {
+                rows,
+                cols,
+                index: rows * width + cols,
+                diagonal: rows != 0 && cols != 0,
+            })
+            .collect()
+    }
+
+    /// The neighbours on the map, with the offset that reaches each.
+    fn neighbours<'a>(
+        &'a self,
+        index: u32,
+        offsets: &'a [Offset],
+    ) -> impl Iterator<Item = (u32, &'a Offset)> + 'a {
+        let (row, col) = (index as usize / self.cols, index as usize % self.cols);
+        offsets
+            .iter()
+            .filter(move |o| {
+                row.checked_add_signed(o.rows)
+                    .is_some_and(|r| r < self.rows)
+                    && col
+                        .checked_add_signed(o.cols)
+                        .is_some_and(|c| c < self.cols)
+            })
+            .map(move |o| ((index as isize + o.index) as u32, o))
+    }
+
+    /// The cost of stepping into `index` by the offset.
+    fn entry_cost(&self, index: u32, offset: &Offset, moves: Moves) -> usize {
+        let risk = usize::from(self.risks[index as usize]);
+        match moves {
+            Moves::Diagonal(rule) if offset.diagonal => rule.apply(risk),
+            _ => risk,
+        }
+    }
+
+    pub fn successors(&self, index: u32, offsets: &[Offset], moves: Moves) -> Vec<(u32, usize)> {
+        self.neighbours(index, offsets)
+            .map(|(next, o)| (next, self.entry_cost(next, o, moves)))
+            .collect()
+    }
+}
+
+/// The same search as `find_path` over the flat grid.
+pub fn find_grid_path(
+    grid: &RiskGrid,
+    search: Search,
+    moves: Moves,
+) -> (Option<(Vec<Pos>, usize)>, usize) {
+    let offsets = grid.offsets(moves);
+    let goal_index = (grid.risks.len() - 1) as u32;
+    let goal = grid.pos(goal_index);
+    let mut expanded = 0;
+    let mut expand = |i: &u32| {
+        expanded += 1;
+        grid.successors(*i, &offsets, moves)
+    };
+    let heuristic = match moves {
+        Moves::Orthogonal => manhattan,
+        Moves::Diagonal(_) => chebyshev,
+    };
+    let path = match search {
+        Search::Dijkstra => dijkstra(&0, &mut expand, |i| *i == goal_index),
+        Search::AStar => astar(
+            &0,
+            &mut expand,
+            |i| heuristic(&grid.pos(*i), &goal),
+            |i| *i == goal_index,
+        ),
+    };
+    let path = path.map(|(path, risk)| (path.into_iter().map(|i| grid.pos(i)).collect(), risk));
+    (path, expanded)
+}
+
+/// Dijkstra's algorithm run from the top left and from the goal at once,
+/// stopping once the two searches meet on the lowest risk path. Returns the
+/// path with its risk, along with how many nodes the two searches expanded.
+pub fn find_bidirectional_path(
+    grid: &RiskGrid,
+    moves: Moves,
+) -> (Option<(Vec<Pos>, usize)>, usize) {
+    const FORWARD: usize = 0;
+    const BACKWARD: usize = 1;
+    let offsets = grid.offsets(moves);
+    let nodes = grid.risks.len();
+    let goal = (nodes - 1) as u32;
+
+    let mut risk = [vec![usize::MAX; nodes], vec![usize::MAX; nodes]];
+    let mut parent = [vec![u32::MAX; nodes], vec![u32::MAX; nodes]];
+    let mut settled = [vec![false; nodes], vec![false; nodes]];
+    let mut frontier = [BinaryHeap::new(), BinaryHeap::new()];
+    risk[FORWARD][0] = 0;
+    risk[BACKWARD][goal as usize] = 0;
+    frontier[FORWARD].push(Reverse((0, 0)));
+    frontier[BACKWARD].push(Reverse((0, goal)));
+    // The lowest risk found so far through a node reached from both ends.
+    let mut best: Option<(usize, u32)> = (goal == 0).then_some((0, 0));
+    let mut expanded = 0;
+
+    while let (Some(Reverse((forward, _))), Some(Reverse((backward, _)))) =
+        (frontier[FORWARD].peek(), frontier[BACKWARD].peek())
+    {
+        let (forward, backward) = (*forward, *backward);
+        if best.is_some_and(|(best, _)| forward + backward >= best) {
+            break;
+        }
+        let side = if forward <= backward {
+            FORWARD
+        } else {
+            BACKWARD
+        };
+        let Some(Reverse((node_risk, node))) = frontier[side].pop() else {
+            break;
+        };
+        if settled[side][node as usize] {
+            continue;
+        }
+        settled[side][node as usize] = true;
+        expanded += 1;
+
+        for (next, offset) in grid.neighbours(node, &offsets) {
+            // Searching backward follows each step the wrong way round, so
+            // its cost is that of stepping into the node it came from.
+            let step = match side {
+                FORWARD => grid.entry_cost(next, offset, moves),
+                _ => grid.entry_cost(node, offset, moves),
+            };
+            let next_risk = node_risk + step;
+            if next_risk < risk[side][next as usize] {
+                risk[side][next as usize] = next_risk;
+                parent[side][next as usize] = node;
+                frontier[side].push(Reverse((next_risk, next)));
+            }
+            let other = risk[1 - side][next as usize];
+            if other != usize::MAX {
+                let through = risk[side][next as usize] + other;
+                if best.is_none_or(|(best, _)| through < best) {
+                    best = Some((through, next));
+                }
+            }
+        }
+    }
+
+    let path = best.map(|(total, meeting)| {
+        let mut path = vec![meeting];
+        while let Some(&node) = path.last().filter(|node| **node != 0) {
+            path.push(parent[FORWARD][node as usize]);
+        }
+        path.reverse();
+        while let Some(&node) = path.last().filter(|node| **node != goal) {
+            path.push(parent[BACKWARD][node as usize]);
+        }
+        (path.into_iter().map(|i| grid.pos(i)).collect(), total)
+    });
+    (path, expanded)
+}
+
+/// The lowest risk path across the map tiled `factor` times each way, and
+/// its total risk, failing if the tiled map is too big to search.
+pub fn lowest_risk(grid: &RiskGrid, factor: usize) -> Result<Option<(Vec<Pos>, usize)>, String> {
+    let tiled = grid.tiled(factor)?;
+    Ok(find_grid_path(&tiled, Search::Dijkstra, Moves::Orthogonal).0)
+}
+
+/// The path across the map tiled `factor` times as a JSON object, giving
+/// the `factor`, the total `risk` and the `path` as `[row, col]` pairs.
+pub fn path_json(path: &[Pos], risk: usize, factor: usize) -> String {
+    let steps = path
+        .iter()
+        .map(|p| format!("[{}, {}]", p.0, p.1))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{{\"factor\": {}, \"risk\": {}, \"path\": [{}]}}",
+        factor, risk, steps
+    )
+}
+
+/// The widest and tallest `--show-path` draws a map before shrinking it.
+pub const MAX_DRAWING: usize = 100;
+
+/// Draws the risks with the path in bold yellow and the rest dimmed. Maps
+/// bigger than `max_size` are shrunk, each character standing for a square
+/// block of positions with their mean risk, highlighted if the path passes
+/// through the block.
+pub fn render_path(path: &[Pos], grid: &RiskGrid, max_size: usize) -> String {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let scale = cmp::max(rows, cols).div_ceil(max_size);
+    let on_path: HashSet<&Pos> = path.iter().collect();
+
+    let mut drawing = String::new();
+    for block_row in 0..rows.div_ceil(scale) {
+        for block_col in 0..cols.div_ceil(scale) {
+            let (mut total, mut count, mut highlighted) = (0, 0, false);
+            for row in block_row * scale..cmp::min((block_row + 1) * scale, rows) {
+                for col in block_col * scale..cmp::min((block_col + 1) * scale, cols) {
+                    let p = Pos(row, col);
+                    highlighted |= on_path.contains(&p);
+                    total += grid.risk(&p);
+                    count += 1;
+                }
+            }
+            let risk = (total + count / 2) / count;
+            if highlighted {
+                drawing.push_str(&format!("\x1b[1;93m{}\x1b[0m", risk));
+            } else {
+                drawing.push_str(&format!("\x1b[2m{}\x1b[0m", risk));
+            }
+        }
+        drawing.push('\n');
+    }
+    drawing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_risk_levels() -> Vec<Vec<usize>> {
+        vec![
+            vec![1, 1, 6, 3, 7, 5, 1, 7, 4, 2],
+            vec![1, 3, 8, 1, 3, 7, 3, 6, 7, 2],
+            vec![2, 1, 3, 6, 5, 1, 1, 3, 2, 8],
+            vec![3, 6, 9, 4, 9, 3, 1, 5, 6, 9],
+            vec![7, 4, 6, 3, 4, 1, 7, 1, 1, 1],
+            vec![1, 3, 1, 9, 1, 2, 8, 1, 3, 7],
+            vec![1, 3, 5, 9, 9, 1, 2, 4, 2, 1],
+            vec![3, 1, 2, 5, 4, 2, 1, 6, 3, 9],
+            vec![1, 2, 9, 3, 1, 3, 8, 5, 2, 1],
+            vec![2, 3, 1, 1, 9, 4, 4, 5, 8, 1],
+        ]
+    }
+
+    #[test]
+
+    fn check_cost_edge() {
+        let risk_levels = sample_risk_levels();
+        assert_eq!(cost_edge(Pos(0, 0), &risk_levels), (Pos(0, 0), 1));
+        assert_eq!(cost_edge(Pos(1, 2), &risk_levels), (Pos(1, 2), 8));
+        assert_eq!(cost_edge(Pos(5, 5), &risk_levels), (Pos(5, 5), 2));
+        assert_eq!(cost_edge(Pos(9, 8), &risk_levels), (Pos(9, 8), 8));
+        assert_eq!(cost_edge(Pos(9, 9), &risk_levels), (Pos(9, 9), 1));
+    }
+
+    #[test]
+
+    fn check_cost_large_map_edge() {
+        let risk_levels = sample_risk_levels();
+        assert_eq!(cost_large_map_edge(Pos(0, 0), &risk_levels), (Pos(0, 0), 1));
+        assert_eq!(
+            cost_large_map_edge(Pos(11, 2), &risk_levels),
+            (Pos(11, 2), 8 + 1)
+        );
+        assert_eq!(
+            cost_large_map_edge(Pos(5, 45), &risk_levels),
+            (Pos(5, 45), 2 + 4)
+        );
+        assert_eq!(
+            cost_large_map_edge(Pos(49, 48), &risk_levels),
+            (Pos(49, 48), (8 + 8) - 9)
+        );
+        assert_eq!(
+            cost_large_map_edge(Pos(49, 49), &risk_levels),
+            (Pos(49, 49), 1 + 8)
+        );
+        assert_eq!(
+            cost_large_map_edge(Pos(99, 99), &risk_levels),
+            (Pos(99, 99), (1 + 18) - 18)
+        );
+        assert_eq!(
+            cost_large_map_edge(Pos(99, 98), &risk_levels),
+            (Pos(99, 98), (8 + 18) - 18)
+        );
+    }
+
+    #[test]
+
+    fn check_successors() {
+        let edge = Pos(9, 9);
+        let cost = |po: Pos| (po, 1);
+
+        assert_eq!(
+            successors(&Pos(0, 0), &edge, cost),
+            vec![(Pos(1, 0), 1), (Pos(0, 1), 1)]
+        );
+        assert_eq!(
+            successors(&Pos(2, 2), &edge, cost),
+            vec![
+                (Pos(3, 2), 1),
+                (Pos(2, 3), 1),
+                (Pos(1, 2), 1),
+                (Pos(2, 1), 1)
+            ]
+        );
+        assert_eq!(
+            successors(&Pos(0, 9), &edge, cost),
+            vec![(Pos(1, 9), 1), (Pos(0, 8), 1)]
+        );
+        assert_eq!(
+            successors(&Pos(9, 0), &edge, cost),
+            vec![(Pos(9, 1), 1), (Pos(8, 0), 1)]
+        );
+        assert_eq!(
+            successors(&Pos(9, 9), &edge, cost),
+            vec![(Pos(8, 9), 1), (Pos(9, 8), 1)]
+        );
+    }
+
+    #[test]
+
+    fn check_shortest_paths() {
+        let risk_levels = sample_risk_levels();
+        let (width, height) = (risk_levels.len(), risk_levels[0].len());
+
+        let goal: Pos = Pos(width - 1, height - 1);
+        let shortest_path = dijkstra(
+            &Pos(0, 0),
+            |p| successors(p, &goal, |po| cost_edge(po, &risk_levels)),
+            |p| *p == goal,
+        );
+
+        assert_eq!(
+            shortest_path,
+            Some((
+                vec![
+                    Pos(0, 0),
+                    Pos(1, 0),
+                    Pos(2, 0),
+                    Pos(2, 1),
+                    Pos(2, 2),
+                    Pos(2, 3),
+                    Pos(2, 4),
+                    Pos(2, 5),
+                    Pos(2, 6),
+                    Pos(3, 6),
+                    Pos(3, 7),
+                    Pos(4, 7),
+                    Pos(5, 7),
+                    Pos(5, 8),
+                    Pos(6, 8),
+                    Pos(7, 8),
+                    Pos(8, 8),
+                    Pos(8, 9),
+                    Pos(9, 9)
+                ],
+                40
+            ))
+        );
+
+        let goal: Pos = Pos(width * 5 - 1, height * 5 - 1);
+        let shortest_path = dijkstra(
+            &Pos(0, 0),
+            |p| successors(p, &goal, |po| cost_large_map_edge(po, &risk_levels)),
+            |p| *p == goal,
+        );
+
+        assert_eq!(
+            shortest_path,
+            Some((
+                vec![
+                    Pos(0, 0),
+                    Pos(1, 0),
+                    Pos(2, 0),
+                    Pos(3, 0),
+                    Pos(4, 0),
+                    Pos(5, 0),
+                    Pos(6, 0),
+                    Pos(7, 0),
+                    Pos(8, 0),
+                    Pos(9, 0),
+                    Pos(10, 0),
+                    Pos(11, 0),
+                    Pos(12, 0),
+                    Pos(12, 1),
+                    Pos(12, 2),
+                    Pos(13, 2),
+                    Pos(14, 2),
+                    Pos(15, 2),
+                    Pos(15, 3),
+                    Pos(16, 3),
+                    Pos(16, 4),
+                    Pos(16, 5),
+                    Pos(16, 6),
+                    Pos(16, 7),
+                    Pos(16, 8),
+                    Pos(16, 9),
+                    Pos(17, 9),
+                    Pos(18, 9),
+                    Pos(18, 10),
+                    Pos(18, 11),
+                    Pos(18, 12),
+                    Pos(19, 12),
+                    Pos(19, 13),
+                    Pos(19, 14),
+                    Pos(20, 14),
+                    Pos(21, 14),
+                    Pos(21, 15),
+                    Pos(22, 15),
+                    Pos(22, 16),
+                    Pos(23, 16),
+                    Pos(24, 16),
+                    Pos(25, 16),
+                    Pos(25, 17),
+                    Pos(25, 18),
+                    Pos(25, 19),
+                    Pos(26, 19),
+                    Pos(27, 19),
+                    Pos(28, 19),
+                    Pos(28, 20),
+                    Pos(28, 21),
+                    Pos(28, 22),
+                    Pos(29, 22),
+                    Pos(29, 23),
+                    Pos(29, 24),
+                    Pos(30, 24),
+                    Pos(30, 25),
+                    Pos(30, 26),
+                    Pos(30, 27),
+                    Pos(31, 27),
+                    Pos(32, 27),
+                    Pos(33, 27),
+                    Pos(33, 28),
+                    Pos(33, 29),
+                    Pos(34, 29),
+                    Pos(34, 30),
+                    Pos(34, 31),
+                    Pos(34, 32),
+                    Pos(35, 32),
+                    Pos(36, 32),
+                    Pos(36, 33),
+                    Pos(37, 33),
+                    Pos(37, 34),
+                    Pos(38, 34),
+                    Pos(39, 34),
+                    Pos(39, 35),
+                    Pos(39, 36),
+                    Pos(39, 37),
+                    Pos(40, 37),
+                    Pos(41, 37),
+                    Pos(42, 37),
+                    Pos(43, 37),
+                    Pos(43, 38),
+                    Pos(43, 39),
+                    Pos(43, 40),
+                    Pos(43, 41),
+                    Pos(44, 41),
+                    Pos(45, 41),
+                    Pos(46, 41),
+                    Pos(46, 42),
+                    Pos(47, 42),
+                    Pos(47, 43),
+                    Pos(47, 44),
+                    Pos(47, 45),
+                    Pos(48, 45),
+                    Pos(49, 45),
+                    Pos(49, 46),
+                    Pos(49, 47),
+                    Pos(49, 48),
+                    Pos(49, 49)
+                ],
+                315
+            ))
+        );
+    }
+
+    #[test]
+    fn check_find_path_with_astar() {
+        let risk_levels = sample_risk_levels();
+        let goal = Pos(49, 49);
+        let cost = |po| cost_large_map_edge(po, &risk_levels);
+
+        let (dijkstra_path, dijkstra_expanded) =
+            find_path(&goal, cost, Search::Dijkstra, Moves::Orthogonal);
+        let (astar_path, astar_expanded) = find_path(&goal, cost, Search::AStar, Moves::Orthogonal);
+
+        assert_eq!(dijkstra_path.map(|(_, risk)| risk), Some(315));
+        assert_eq!(astar_path.map(|(_, risk)| risk), Some(315));
+        assert!(astar_expanded <= dijkstra_expanded);
+        assert_eq!(manhattan(&Pos(3, 7), &goal), 46 + 42);
+    }
+
+    #[test]
+    fn check_diagonal_successors() {
+        let edge = Pos(9, 9);
+        let cost = |po: Pos| (po, 2);
+
+        assert_eq!(
+            diagonal_successors(&Pos(0, 0), &edge, cost, DiagonalCost::Extra(1)),
+            vec![(Pos(1, 0), 2), (Pos(0, 1), 2), (Pos(1, 1), 3)]
+        );
+        assert_eq!(
+            diagonal_successors(&Pos(9, 2), &edge, cost, DiagonalCost::Double),
+            vec![
+                (Pos(9, 3), 2),
+                (Pos(8, 2), 2),
+                (Pos(9, 1), 2),
+                (Pos(8, 3), 4),
+                (Pos(8, 1), 4)
+            ]
+        );
+        assert_eq!("+3".parse(), Ok(DiagonalCost::Extra(3)));
+        assert!("triple".parse::<DiagonalCost>().is_err());
+    }
+
+    #[test]
+    fn check_find_path_with_diagonals() {
+        let risk_levels = sample_risk_levels();
+        let goal = Pos(9, 9);
+        let cost = |po| cost_edge(po, &risk_levels);
+
+        for search in [Search::Dijkstra, Search::AStar] {
+            let same = find_path(&goal, cost, search, Moves::Diagonal(DiagonalCost::Same));
+            let costly = find_path(
+                &goal,
+                cost,
+                search,
+                Moves::Diagonal(DiagonalCost::Extra(100)),
+            );
+            assert!(same.0.unwrap().1 < 40);
+            assert_eq!(costly.0.map(|(_, risk)| risk), Some(40));
+        }
+    }
+
+    #[test]
+    fn check_find_grid_path() {
+        let risk_levels = sample_risk_levels();
+        for factor in [1, 5] {
+            let grid = RiskGrid::from_rows(&risk_levels)
+                .unwrap()
+                .tiled(factor)
+                .unwrap();
+            let goal = Pos(10 * factor - 1, 10 * factor - 1);
+            for moves in [Moves::Orthogonal, Moves::Diagonal(DiagonalCost::Extra(2))] {
+                for search in [Search::Dijkstra, Search::AStar] {
+                    assert_eq!(
+                        find_grid_path(&grid, search, moves),
+                        find_path(
+                            &goal,
+                            |po| cost_large_map_edge(po, &risk_levels),
+                            search,
+                            moves
+                        )
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_find_bidirectional_path() {
+        let risk_levels = sample_risk_levels();
+        for factor in [1, 5] {
+            let grid = RiskGrid::from_rows(&risk_levels)
+                .unwrap()
+                .tiled(factor)
+                .unwrap();
+            for moves in [Moves::Orthogonal, Moves::Diagonal(DiagonalCost::Double)] {
+                let (expected, expected_expanded) = find_grid_path(&grid, Search::Dijkstra, moves);
+                let (path, expanded) = find_bidirectional_path(&grid, moves);
+                let (path, risk) = path.unwrap();
+
+                assert_eq!(Some(risk), expected.map(|(_, risk)| risk));
+                assert!(expanded <= expected_expanded);
+                assert_eq!(path.first(), Some(&Pos(0, 0)));
+                assert_eq!(path.last(), Some(&grid.pos(grid.risks.len() as u32 - 1)));
+                let offsets = grid.offsets(moves);
+                let walked: usize = path
+                    .windows(2)
+                    .map(|step| {
+                        let (from, to) = (
+                            step[0].0 * grid.cols + step[0].1,
+                            step[1].0 * grid.cols + step[1].1,
+                        );
+                        grid.successors(from as u32, &offsets, moves)
+                            .into_iter()
+                            .find(|(next, _)| *next as usize == to)
+                            .unwrap()
+                            .1
+                    })
+                    .sum();
+                assert_eq!(walked, risk);
+            }
+        }
+
+        let single = RiskGrid::from_rows(&[vec![3]]).unwrap();
+        assert_eq!(
+            find_bidirectional_path(&single, Moves::Orthogonal).0,
+            Some((vec![Pos(0, 0)], 0))
+        );
+    }
+
+    #[test]
+    fn check_lowest_risk() {
+        let lines = sample_risk_levels()
+            .iter()
+            .map(|row| row.iter().map(|risk| risk.to_string()).collect())
+            .collect::<Vec<String>>();
+        let grid = RiskGrid::parse(&lines).unwrap();
+
+        assert_eq!(grid.to_rows(), sample_risk_levels());
+        assert_eq!(
+            lowest_risk(&grid, 1).map(|path| path.map(|(_, risk)| risk)),
+            Ok(Some(40))
+        );
+        let (path, risk) = lowest_risk(&grid, 5).unwrap().unwrap();
+        assert_eq!((path.len(), risk), (99, 315));
+        assert_eq!(grid.tiled(5).unwrap().risk(&Pos(49, 48)), 8 + 8 - 9);
+
+        assert!(RiskGrid::parse(&["12".to_owned(), "1".to_owned()]).is_err());
+        assert!(RiskGrid::parse(&["1a".to_owned()]).is_err());
+        assert!(RiskGrid::parse(&[]).is_err());
+        assert_eq!(
+            RiskGrid::from_rows(&[vec![1, 2], vec![256, 3]]).err(),
+            Some("Bad risk level 256 at row 1 col 0".to_owned())
+        );
+        assert!(RiskGrid::from_rows(&[vec![0]]).is_err());
+    }
+
+    #[test]
+    fn check_path_json() {
+        assert_eq!(
+            path_json(&[Pos(0, 0), Pos(1, 0), Pos(1, 1)], 4, 1),
+            "{\"factor\": 1, \"risk\": 4, \"path\": [[0, 0], [1, 0], [1, 1]]}"
+        );
+    }
+
+    #[test]
+    fn check_render_path() {
+        let grid = RiskGrid::from_rows(&sample_risk_levels()).unwrap();
+        let path = vec![Pos(0, 0), Pos(1, 0), Pos(2, 0)];
+
+        let drawing = render_path(&path, &grid, 10);
+        let first_row = drawing.lines().next().unwrap();
+        assert!(first_row.starts_with("\x1b[1;93m1\x1b[0m\x1b[2m1\x1b[0m\x1b[2m6\x1b[0m"));
+        assert_eq!(drawing.lines().count(), 10);
+
+        let shrunk = render_path(&path, &grid, 5);
+        let rows = shrunk
+            .replace("\x1b[1;93m", "")
+            .replace("\x1b[2m", "")
+            .replace("\x1b[0m", "");
+        assert_eq!(rows.lines().next(), Some("25644"));
+        assert_eq!(rows.lines().count(), 5);
+        assert!(shrunk.starts_with("\x1b[1;93m2\x1b[0m\x1b[2m5\x1b[0m"));
+        assert!(shrunk.lines().nth(1).unwrap().starts_with("\x1b[1;93m"));
+    }
+}
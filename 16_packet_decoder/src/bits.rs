@@ -0,0 +1,87 @@
+use crate::PacketError;
+use std::io::{ErrorKind, Read};
+
+/// A source of bits, read most significant first.
+pub trait BitRead {
+    /// Reads the next bit.
+    fn read_bit(&mut self) -> Result<u8, PacketError>;
+
+    /// How many bits have been read so far.
+    fn position(&self) -> usize;
+
+    /// Reads the next `count` bits as a number.
+    fn read_number(&mut self, count: usize) -> Result<u64, PacketError> {
+        (0..count).try_fold(0, |v, _| Ok((v << 1) + u64::from(self.read_bit()?)))
+    }
+}
+
+/// Reads the bits of a slice of nibbles, as decoded from hex.
+pub struct Nibbles<'a> {
+    nibbles: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> Nibbles<'a> {
+    pub fn new(nibbles: &'a [u8]) -> Self {
+        Nibbles {
+            nibbles,
+            bit_pos: 0,
+        }
+    }
+}
+
+impl BitRead for Nibbles<'_> {
+    fn read_bit(&mut self) -> Result<u8, PacketError> {
+        let bit_pos = self.bit_pos;
+        let bit = self
+            .nibbles
+            .get(bit_pos / 4)
+            .map(|nibble| nibble >> (3 - (bit_pos % 4)) & 0x1)
+            .ok_or(PacketError::Truncated { bit_pos })?;
+        self.bit_pos += 1;
+        Ok(bit)
+    }
+
+    fn position(&self) -> usize {
+        self.bit_pos
+    }
+}
+
+/// Reads the bits of raw bytes, a byte at a time, from any source.
+pub struct BitStream<R> {
+    source: R,
+    byte: u8,
+    bit_pos: usize,
+}
+
+impl<R: Read> BitStream<R> {
+    pub fn new(source: R) -> Self {
+        BitStream {
+            source,
+            byte: 0,
+            bit_pos: 0,
+        }
+    }
+}
+
+impl<R: Read> BitRead for BitStream<R> {
+    fn read_bit(&mut self) -> Result<u8, PacketError> {
+        let bit_pos = self.bit_pos;
+        if bit_pos.is_multiple_of(8) {
+            let mut buf = [0];
+            self.source
+                .read_exact(&mut buf)
+                .map_err(|err| match err.kind() {
+                    ErrorKind::UnexpectedEof => PacketError::Truncated { bit_pos },
+                    _ => PacketError::Read(err),
+                })?;
+            self.byte = buf[0];
+        }
+        self.bit_pos += 1;
+        Ok(self.byte >> (7 - (bit_pos % 8)) & 0x1)
+    }
+
+    fn position(&self) -> usize {
+        self.bit_pos
+    }
+}
@@ -1,5 +1,9 @@
+mod bits;
+
+use bits::{BitRead, BitStream, Nibbles};
 use std::io::{BufRead, BufReader};
 use std::num::TryFromIntError;
+use std::ops::Range;
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -14,6 +18,137 @@ enum PacketError {
         value: u64,
     },
     InvalidTryFrom(TryFromIntError),
+    /// The packet runs on past the end of the transmission.
+    Truncated {
+        bit_pos: usize,
+    },
+    /// The transmission couldn't be read.
+    Read(std::io::Error),
+}
+
+impl std::fmt::Display for PacketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PacketError::InvalidHexCharacter { index, c } => {
+                write!(f, "Encountered invalid hex char '{}' at {}", c, index)
+            }
+            PacketError::InvalidPacketType { value } => {
+                write!(f, "Encountered invalid package type value {}", value)
+            }
+            PacketError::InvalidTryFrom(err) => {
+                write!(f, "Could not convert the following {}", err)
+            }
+            PacketError::Truncated { bit_pos } => {
+                write!(f, "The transmission ended early, at bit {}", bit_pos)
+            }
+            PacketError::Read(err) => write!(f, "Could not read the transmission: {}", err),
+        }
+    }
+}
+
+/// Why a packet can't be written as BITS.
+#[derive(Debug, PartialEq, Eq)]
+enum EncodeError {
+    /// Versions are held in 3 bits.
+    VersionTooLarge(u8),
+    /// Neither length type can hold this many sub-packets or bits of them.
+    TooManySubPackets { count: usize, bits: usize },
+    /// A literal's content isn't a value, or an operator's isn't packets.
+    ContentMismatch,
+}
+
+impl std::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::VersionTooLarge(version) => {
+                write!(f, "Version {} doesn't fit in 3 bits", version)
+            }
+            EncodeError::TooManySubPackets { count, bits } => write!(
+                f,
+                "{} sub-packets taking {} bits is too many for either length type",
+                count, bits
+            ),
+            EncodeError::ContentMismatch => {
+                write!(f, "The packet's content doesn't match its type")
+            }
+        }
+    }
+}
+
+/// Why a packet has no value.
+#[derive(Debug, PartialEq, Eq)]
+enum EvalError {
+    /// A literal holding packets, or an operator holding a value.
+    TypeMismatch { packet_type: PacketType },
+    /// Comparisons take two operands, and the other operators at least one.
+    OperandCount {
+        packet_type: PacketType,
+        found: usize,
+    },
+    /// The value doesn't fit in 64 bits.
+    Overflow { packet_type: PacketType },
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::TypeMismatch { packet_type } => {
+                write!(f, "A {:?} packet has the wrong content", packet_type)
+            }
+            EvalError::OperandCount { packet_type, found } => {
+                let expected = match packet_type {
+                    PacketType::GtProduct | PacketType::LtProduct | PacketType::EqProduct => "2",
+                    _ => "at least 1",
+                };
+                write!(
+                    f,
+                    "A {:?} packet has {} operands, expected {}",
+                    packet_type, found, expected
+                )
+            }
+            EvalError::Overflow { packet_type } => {
+                write!(f, "A {:?} packet's value is too big", packet_type)
+            }
+        }
+    }
+}
+
+/// How an operator packet gives the extent of its sub-packets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LengthType {
+    /// Length type 0, the total bits of the sub-packets in 15 bits.
+    TotalBits,
+    /// Length type 1, the number of sub-packets in 11 bits.
+    SubPacketCount,
+}
+
+#[derive(Debug, PartialEq)]
+struct PacketVisit<'a> {
+    packet: &'a Packet,
+    depth: usize,
+}
+
+/// Visits the packets depth first, each before its sub-packets.
+struct PacketVisitor<'a> {
+    stack: Vec<PacketVisit<'a>>,
+}
+
+impl<'a> Iterator for PacketVisitor<'a> {
+    type Item = PacketVisit<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet_visit = self.stack.pop()?;
+
+        if let PacketContent::SubPackets(sub_packets) = &packet_visit.packet.content {
+            self.stack
+                .extend(sub_packets.iter().rev().map(|packet| PacketVisit {
+                    packet,
+                    depth: packet_visit.depth + 1,
+                }));
+        }
+
+        Some(packet_visit)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -23,7 +158,7 @@ struct Packet {
     content: PacketContent,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum PacketType {
     Sum,
     Product,
@@ -35,6 +170,21 @@ enum PacketType {
     EqProduct,
 }
 
+impl From<&PacketType> for u64 {
+    fn from(packet_type: &PacketType) -> Self {
+        match packet_type {
+            PacketType::Sum => 0,
+            PacketType::Product => 1,
+            PacketType::MinProduct => 2,
+            PacketType::MaxProduct => 3,
+            PacketType::Literal => 4,
+            PacketType::GtProduct => 5,
+            PacketType::LtProduct => 6,
+            PacketType::EqProduct => 7,
+        }
+    }
+}
+
 impl TryFrom<u64> for PacketType {
     type Error = PacketError;
 
@@ -63,140 +213,297 @@ impl FromStr for Packet {
     type Err = PacketError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let nibbles = s
-            .chars()
-            .enumerate()
-            .map(|(idx, c)| hex_char_to_nibble(c as u8, idx))
-            .collect::<Result<Vec<u8>, Self::Err>>()?;
-        extract_packet(&nibbles, 0).map(|p| p.0)
+        parse_with_spans(s).map(|p| p.0)
     }
 }
 
-fn extract_packet(payload: &[u8], bit_pos: usize) -> Result<(Packet, usize), PacketError> {
-    let version = u8::try_from(get_number(payload, bit_pos, 3))
-        .map_err(|a| PacketError::InvalidTryFrom(a))?;
+/// Parses the hex transmission, also giving the bits each packet spans in
+/// the order the packets appear.
+fn parse_with_spans(s: &str) -> Result<(Packet, Vec<Range<usize>>), PacketError> {
+    let nibbles = s
+        .chars()
+        .enumerate()
+        .map(|(idx, c)| hex_char_to_nibble(c as u8, idx))
+        .collect::<Result<Vec<u8>, PacketError>>()?;
+    read_with_spans(&mut Nibbles::new(&nibbles))
+}
+
+/// Reads the outermost packet from the bits, also giving the bits each
+/// packet spans in the order the packets appear.
+fn read_with_spans(bits: &mut impl BitRead) -> Result<(Packet, Vec<Range<usize>>), PacketError> {
+    let mut spans = Vec::new();
+    let packet = extract_packet(bits, &mut spans)?;
+    Ok((packet, spans))
+}
+
+/// The packet tree, one packet per line indented by depth, with the bits
+/// each packet spans.
+fn dump(packet: &Packet, spans: &[Range<usize>]) -> String {
+    packet
+        .visitor_iter()
+        .zip(spans)
+        .map(|(PacketVisit { packet, depth }, span)| {
+            let value = match packet.content {
+                PacketContent::Literal(value) => format!(" = {}", value),
+                PacketContent::SubPackets(_) => String::new(),
+            };
+            format!(
+                "{}{:?} v{}{} (bits {}..{}, {} long)\n",
+                "  ".repeat(depth),
+                packet.packet_type,
+                packet.version,
+                value,
+                span.start,
+                span.end,
+                span.len()
+            )
+        })
+        .collect()
+}
 
-    let packet_type = PacketType::try_from(get_number(payload, bit_pos + 3, 3))?;
-    let (content, bit_pos) = if packet_type == PacketType::Literal {
-        read_literal(&payload, bit_pos + 6, 0)
+impl Packet {
+    fn visitor_iter(&self) -> PacketVisitor<'_> {
+        PacketVisitor {
+            stack: vec![PacketVisit {
+                packet: self,
+                depth: 0,
+            }],
+        }
+    }
+
+    /// The packet as hex, picking the shorter length type for each operator.
+    fn encode(&self) -> Result<String, EncodeError> {
+        self.encode_with(None)
+    }
+
+    /// The packet as hex, using the preferred length type for operators
+    /// where it can hold their sub-packets.
+    fn encode_with(&self, preference: Option<LengthType>) -> Result<String, EncodeError> {
+        let mut bits = Vec::new();
+        self.encode_bits(preference, &mut bits)?;
+        Ok(bits
+            .chunks(4)
+            .map(|nibble| {
+                let value = (0..4).fold(0, |v, i| {
+                    (v << 1) + u32::from(nibble.get(i).copied().unwrap_or(false))
+                });
+                std::char::from_digit(value, 16)
+                    .unwrap_or('0')
+                    .to_ascii_uppercase()
+            })
+            .collect())
+    }
+
+    /// Appends the packet's BITS to `bits`.
+    fn encode_bits(
+        &self,
+        preference: Option<LengthType>,
+        bits: &mut Vec<bool>,
+    ) -> Result<(), EncodeError> {
+        if self.version > 7 {
+            return Err(EncodeError::VersionTooLarge(self.version));
+        }
+        push_number(bits, u64::from(self.version), 3);
+        push_number(bits, u64::from(&self.packet_type), 3);
+        match (&self.packet_type, &self.content) {
+            (PacketType::Literal, PacketContent::Literal(value)) => {
+                let groups = (1..16).find(|g| value >> (4 * g) == 0).unwrap_or(16);
+                for group in (0..groups).rev() {
+                    bits.push(group > 0);
+                    push_number(bits, value >> (4 * group), 4);
+                }
+                Ok(())
+            }
+            (PacketType::Literal, _) | (_, PacketContent::Literal(_)) => {
+                Err(EncodeError::ContentMismatch)
+            }
+            (_, PacketContent::SubPackets(sub_packets)) => {
+                let mut sub_bits = Vec::new();
+                for sub_packet in sub_packets {
+                    sub_packet.encode_bits(preference, &mut sub_bits)?;
+                }
+                let fits_count = sub_packets.len() < 1 << 11;
+                let fits_bits = sub_bits.len() < 1 << 15;
+                let length_type = match (preference, fits_count, fits_bits) {
+                    (Some(LengthType::TotalBits), _, true) | (_, false, true) => {
+                        LengthType::TotalBits
+                    }
+                    (_, true, _) => LengthType::SubPacketCount,
+                    (_, false, false) => {
+                        return Err(EncodeError::TooManySubPackets {
+                            count: sub_packets.len(),
+                            bits: sub_bits.len(),
+                        })
+                    }
+                };
+                match length_type {
+                    LengthType::TotalBits => {
+                        bits.push(false);
+                        push_number(bits, sub_bits.len() as u64, 15);
+                    }
+                    LengthType::SubPacketCount => {
+                        bits.push(true);
+                        push_number(bits, sub_packets.len() as u64, 11);
+                    }
+                }
+                bits.extend(sub_bits);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Appends the lowest `count` bits of `value`, most significant first.
+fn push_number(bits: &mut Vec<bool>, value: u64, count: usize) {
+    bits.extend((0..count).rev().map(|i| (value >> i) & 1 == 1));
+}
+
+fn extract_packet(
+    bits: &mut impl BitRead,
+    spans: &mut Vec<Range<usize>>,
+) -> Result<Packet, PacketError> {
+    let start = bits.position();
+    let span = spans.len();
+    spans.push(start..start);
+    let version = u8::try_from(bits.read_number(3)?).map_err(PacketError::InvalidTryFrom)?;
+
+    let packet_type = PacketType::try_from(bits.read_number(3)?)?;
+    let content = if packet_type == PacketType::Literal {
+        read_literal(bits, 0)
     } else {
-        read_operation(&payload, bit_pos + 6)
+        read_operation(bits, spans)
     }?;
+    spans[span] = start..bits.position();
     let packet = Packet {
         version,
         packet_type,
         content,
     };
-    Ok((packet, bit_pos))
+    Ok(packet)
 }
 
-fn read_literal(
-    payload: &[u8],
-    bit_pos: usize,
-    current_val: u64,
-) -> Result<(PacketContent, usize), PacketError> {
-    let has_more = get_bit(payload, bit_pos) != 0;
-    let val = (current_val << 4) + get_number(payload, bit_pos + 1, 4);
+fn read_literal(bits: &mut impl BitRead, current_val: u64) -> Result<PacketContent, PacketError> {
+    let has_more = bits.read_bit()? != 0;
+    let val = (current_val << 4) + bits.read_number(4)?;
     if has_more {
-        read_literal(payload, bit_pos + 5, val)
+        read_literal(bits, val)
     } else {
-        Ok((PacketContent::Literal(val), bit_pos + 5))
+        Ok(PacketContent::Literal(val))
     }
 }
 
-fn read_operation(payload: &[u8], bit_pos: usize) -> Result<(PacketContent, usize), PacketError> {
-    let know_number_of_sub_packets = get_bit(payload, bit_pos) != 0;
+fn read_operation(
+    bits: &mut impl BitRead,
+    spans: &mut Vec<Range<usize>>,
+) -> Result<PacketContent, PacketError> {
+    let know_number_of_sub_packets = bits.read_bit()? != 0;
     if know_number_of_sub_packets {
-        let total_bits = get_number(payload, bit_pos + 1, 11);
-        let (packets, bit_pos) =
-            (0..total_bits).fold(Ok((Vec::new(), bit_pos + 12)), |r, _| match r {
-                Ok((mut packets, start_bit_pos)) => {
-                    let (packet, ended_bit_pos) = extract_packet(payload, start_bit_pos)?;
-                    packets.push(packet);
-                    Ok((packets, ended_bit_pos))
-                }
-                _ => r,
-            })?;
-        Ok((PacketContent::SubPackets(packets), bit_pos))
+        let total_bits = bits.read_number(11)?;
+        let packets = (0..total_bits)
+            .map(|_| extract_packet(bits, spans))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PacketContent::SubPackets(packets))
     } else {
-        let total_bits = get_number(payload, bit_pos + 1, 15);
-        let mut bit_pos = bit_pos + 16;
+        let total_bits = bits.read_number(15)?;
         let goal =
-            bit_pos + usize::try_from(total_bits).map_err(|a| PacketError::InvalidTryFrom(a))?;
+            bits.position() + usize::try_from(total_bits).map_err(PacketError::InvalidTryFrom)?;
         let mut packets = Vec::new();
-        while bit_pos < goal {
-            let (packet, ended_bit_pos) = extract_packet(payload, bit_pos)?;
-            packets.push(packet);
-            bit_pos = ended_bit_pos;
+        while bits.position() < goal {
+            packets.push(extract_packet(bits, spans)?);
         }
-        Ok((PacketContent::SubPackets(packets), bit_pos))
+        Ok(PacketContent::SubPackets(packets))
     }
 }
 
 fn summed_versions(packet: &Packet) -> u64 {
-    match &packet.content {
-        PacketContent::Literal(_) => u64::from(packet.version),
-        PacketContent::SubPackets(sp) => sp
-            .iter()
-            .fold(u64::from(packet.version), |s, c| s + summed_versions(c)),
-    }
+    packet
+        .visitor_iter()
+        .map(|visit| u64::from(visit.packet.version))
+        .sum()
 }
 
-fn derived_values(packet: &Packet) -> u64 {
-    match &packet.content {
-        PacketContent::Literal(l) => match &packet.packet_type {
-            PacketType::Literal => l.to_owned(),
-            _ => panic!("Expecting literal type"),
-        },
-        PacketContent::SubPackets(sp) => {
-            let mut values = sp.iter().map(|p| derived_values(p));
-
-            match &packet.packet_type {
-                PacketType::Sum => values.sum(),
-                PacketType::Product => values.product(),
-                PacketType::MinProduct => values.min().unwrap_or(0),
-                PacketType::MaxProduct => values.max().unwrap_or(0),
-                PacketType::GtProduct => {
-                    let m = values.next().unwrap_or(0);
-                    let m2 = values.next().unwrap_or(0);
-                    if m > m2 {
-                        1
-                    } else {
-                        0
-                    }
-                }
-                PacketType::LtProduct => {
-                    let m = values.next().unwrap_or(0);
-                    let m2 = values.next().unwrap_or(0);
-                    if m < m2 {
-                        1
-                    } else {
-                        0
+fn derived_values(packet: &Packet) -> Result<u64, EvalError> {
+    // Every packet comes after its sub-packets when visited in reverse, so
+    // their values are on the stack, the first on top, by the time it's seen.
+    let visits = packet.visitor_iter().collect::<Vec<_>>();
+    let mut stack = Vec::new();
+    for PacketVisit { packet, .. } in visits.into_iter().rev() {
+        let packet_type = packet.packet_type;
+        let value = match &packet.content {
+            PacketContent::Literal(l) => match packet_type {
+                PacketType::Literal => *l,
+                _ => return Err(EvalError::TypeMismatch { packet_type }),
+            },
+            PacketContent::SubPackets(sp) => {
+                let at = stack.len() - sp.len();
+                let values = stack.drain(at..).rev().collect::<Vec<_>>();
+
+                match (packet_type, values.as_slice()) {
+                    (PacketType::Literal, _) => {
+                        return Err(EvalError::TypeMismatch { packet_type })
                     }
-                }
-                PacketType::EqProduct => {
-                    let m = values.next().unwrap_or(0);
-                    let m2 = values.next().unwrap_or(0);
-                    if m == m2 {
-                        1
-                    } else {
-                        0
+                    (PacketType::GtProduct, &[m, m2]) => u64::from(m > m2),
+                    (PacketType::LtProduct, &[m, m2]) => u64::from(m < m2),
+                    (PacketType::EqProduct, &[m, m2]) => u64::from(m == m2),
+                    (PacketType::GtProduct | PacketType::LtProduct | PacketType::EqProduct, _)
+                    | (_, []) => {
+                        return Err(EvalError::OperandCount {
+                            packet_type,
+                            found: values.len(),
+                        })
                     }
+                    (PacketType::Sum, _) => values
+                        .iter()
+                        .try_fold(0_u64, |sum, v| sum.checked_add(*v))
+                        .ok_or(EvalError::Overflow { packet_type })?,
+                    (PacketType::Product, _) => values
+                        .iter()
+                        .try_fold(1_u64, |product, v| product.checked_mul(*v))
+                        .ok_or(EvalError::Overflow { packet_type })?,
+                    (PacketType::MinProduct, _) => values.iter().copied().min().unwrap_or(0),
+                    (PacketType::MaxProduct, _) => values.iter().copied().max().unwrap_or(0),
                 }
-                PacketType::Literal => panic!("Expecting non literal type"),
             }
-        }
+        };
+        stack.push(value);
     }
+    Ok(stack.pop().unwrap_or(0))
 }
 
-fn get_number(payload: &[u8], bit_pos: usize, count: usize) -> u64 {
-    (0..count).fold(0, |v, i| {
-        (v << 1) + u64::from(get_bit(payload, bit_pos + i))
-    })
-}
+/// The packet as an expression, with operators in infix form where they
+/// have one and brackets only where precedence needs them.
+fn formula(packet: &Packet) -> String {
+    // Comparisons bind loosest, then sums, then products, with values and
+    // function calls tightest.
+    fn render(packet: &Packet) -> (String, u8) {
+        let sub_packets = match &packet.content {
+            PacketContent::Literal(value) => return (value.to_string(), 3),
+            PacketContent::SubPackets(sub_packets) => sub_packets,
+        };
+        let operands = |min_precedence| {
+            sub_packets
+                .iter()
+                .map(|sub_packet| match render(sub_packet) {
+                    (text, precedence) if precedence < min_precedence => format!("({})", text),
+                    (text, _) => text,
+                })
+                .collect::<Vec<_>>()
+        };
+        let call = |name: &str| (format!("{}({})", name, operands(0).join(", ")), 3);
+        match (packet.packet_type, sub_packets.len()) {
+            (PacketType::Sum | PacketType::Product, 1) => render(&sub_packets[0]),
+            (PacketType::Sum, 2..) => (operands(1).join(" + "), 1),
+            (PacketType::Product, 2..) => (operands(2).join(" * "), 2),
+            (PacketType::GtProduct, 2) => (operands(1).join(" > "), 0),
+            (PacketType::LtProduct, 2) => (operands(1).join(" < "), 0),
+            (PacketType::EqProduct, 2) => (operands(1).join(" == "), 0),
+            (PacketType::MinProduct, _) => call("min"),
+            (PacketType::MaxProduct, _) => call("max"),
+            (packet_type, _) => call(&format!("{:?}", packet_type).to_lowercase()),
+        }
+    }
 
-fn get_bit(payload: &[u8], bit_pos: usize) -> u8 {
-    payload[bit_pos / 4] >> (3 - (bit_pos % 4)) & 0x1
+    render(packet).0
 }
 
 fn hex_char_to_nibble(c: u8, idx: usize) -> Result<u8, PacketError> {
@@ -211,33 +518,127 @@ fn hex_char_to_nibble(c: u8, idx: usize) -> Result<u8, PacketError> {
     }
 }
 
-fn main() {
-    let lines: Vec<String> = BufReader::new(std::io::stdin())
-        .lines()
-        .filter_map(|f| f.ok())
-        .collect();
-
-    let input = lines[0].clone();
-    let packet_result: Result<Packet, PacketError> = input.parse();
-    match packet_result {
-        Ok(p) => {
+/// Prints what was decoded from one transmission.
+fn report(parsed: Result<(Packet, Vec<Range<usize>>), PacketError>, args: &[String]) {
+    match parsed {
+        Ok((p, spans)) => {
+            if args.iter().any(|a| a == "--dump") {
+                print!("{}", dump(&p, &spans));
+            }
             let summed_versions_score = summed_versions(&p);
             println!("summed versions: {}", summed_versions_score);
-            let derived_values_score = derived_values(&p);
-            println!("derived values score: {}", derived_values_score);
-        }
-        Err(p_err) => match p_err {
-            PacketError::InvalidHexCharacter { index, c } => {
-                println!("Encountered invalid hex char '{}' at {}", c, index);
+            match derived_values(&p) {
+                Ok(derived_values_score) => {
+                    println!("derived values score: {}", derived_values_score)
+                }
+                Err(err) => println!("Could not derive the packet's value: {}", err),
             }
-            PacketError::InvalidPacketType { value } => {
-                println!("Encountered invalid package type value {}", value);
+            if args.iter().any(|a| a == "--formula") {
+                println!("formula: {}", formula(&p));
             }
-            PacketError::InvalidTryFrom(err) => {
-                println!("Could not convert the following {}", err);
+            if args.iter().any(|a| a == "--encode") {
+                match p.encode() {
+                    Ok(hex) => println!("encoded: {}", hex),
+                    Err(err) => println!("Could not encode the packet: {}", err),
+                }
             }
+        }
+        Err(err) => println!("{}", err),
+    }
+}
+
+/// What was decoded from the transmission on the given line, as a JSON
+/// object.
+fn to_json(line_number: usize, parsed: &Result<Packet, PacketError>) -> String {
+    let (summed_versions, value, error) = match parsed {
+        Ok(packet) => match derived_values(packet) {
+            Ok(value) => (
+                summed_versions(packet).to_string(),
+                value.to_string(),
+                "null".to_owned(),
+            ),
+            Err(err) => (
+                summed_versions(packet).to_string(),
+                "null".to_owned(),
+                json_string(&err.to_string()),
+            ),
         },
+        Err(err) => (
+            "null".to_owned(),
+            "null".to_owned(),
+            json_string(&err.to_string()),
+        ),
+    };
+    format!(
+        "{{\"line\": {}, \"summed_versions\": {}, \"value\": {}, \"error\": {}}}",
+        line_number, summed_versions, value, error
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let escaped = s
+        .chars()
+        .map(|ch| match ch {
+            '"' => "\\\"".to_owned(),
+            '\\' => "\\\\".to_owned(),
+            ch if ch.is_control() => format!("\\u{:04x}", ch as u32),
+            ch => ch.to_string(),
+        })
+        .collect::<String>();
+    format!("\"{}\"", escaped)
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let json = match args.iter().position(|a| a == "--format") {
+        Some(i) => match args.get(i + 1).map(String::as_str) {
+            Some("json") => true,
+            Some("text") => false,
+            format => {
+                return Err(format!(
+                    "Unknown format {:?}, expected json or text",
+                    format
+                ))
+            }
+        },
+        None => false,
+    };
+
+    // Each transmission with the line it's on, the whole input being one
+    // transmission when it's raw bytes.
+    let transmissions = if args.iter().any(|a| a == "--binary") {
+        vec![(
+            1,
+            read_with_spans(&mut BitStream::new(std::io::stdin().lock())),
+        )]
+    } else {
+        BufReader::new(std::io::stdin())
+            .lines()
+            .map_while(Result::ok)
+            .zip(1..)
+            .filter(|(line, _)| !line.trim().is_empty())
+            .map(|(line, line_number)| (line_number, parse_with_spans(line.trim())))
+            .collect::<Vec<_>>()
+    };
+
+    if json {
+        let objects = transmissions
+            .into_iter()
+            .map(|(line_number, parsed)| {
+                format!("  {}", to_json(line_number, &parsed.map(|p| p.0)))
+            })
+            .collect::<Vec<_>>();
+        println!("[\n{}\n]", objects.join(",\n"));
+        return Ok(());
+    }
+    let batch = transmissions.len() > 1;
+    for (line_number, parsed) in transmissions {
+        if batch {
+            println!("Line {}:", line_number);
+        }
+        report(parsed, &args);
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -278,7 +679,7 @@ mod tests {
             #[test]
             fn $name() {
                 let packet: Result<Packet, PacketError> = $input.parse();
-                let score = derived_values(&packet.unwrap());
+                let score = derived_values(&packet.unwrap()).unwrap();
 
                 assert_eq!(score, $expected);
             }
@@ -336,4 +737,214 @@ mod tests {
         equal_derived_values("9C0141080250320F1802104A08", summed_value: 1),
 
     }
+
+    #[test]
+    fn check_encode_round_trip() {
+        for hex in [
+            "D2FE28",
+            "38006F45291200",
+            "EE00D40C823060",
+            "9C0141080250320F1802104A08",
+            "A0016C880162017C3686B18A3D4780",
+        ] {
+            let packet: Packet = hex.parse().unwrap();
+            for preference in [
+                None,
+                Some(LengthType::TotalBits),
+                Some(LengthType::SubPacketCount),
+            ] {
+                let encoded = packet.encode_with(preference).unwrap();
+                assert_eq!(encoded.parse::<Packet>().unwrap(), packet);
+            }
+        }
+        assert_eq!(
+            "D2FE28".parse::<Packet>().unwrap().encode().unwrap(),
+            "D2FE28"
+        );
+        let packet: Packet = "38006F45291200".parse().unwrap();
+        assert_eq!(
+            packet.encode_with(Some(LengthType::TotalBits)).unwrap(),
+            "38006F4529120"
+        );
+        assert_eq!(packet.encode().unwrap(), "3A00B4529120");
+    }
+
+    #[test]
+    fn check_binary_stream() {
+        let bytes: &[u8] = &[0x38, 0x00, 0x6F, 0x45, 0x29, 0x12, 0x00];
+        let (packet, spans) = read_with_spans(&mut BitStream::new(bytes)).unwrap();
+        assert_eq!(packet, "38006F45291200".parse().unwrap());
+        assert_eq!(spans, vec![0..49, 22..33, 33..49]);
+        let bytes: &[u8] = &[0x38, 0x00, 0x6F];
+        assert!(matches!(
+            read_with_spans(&mut BitStream::new(bytes)),
+            Err(PacketError::Truncated { bit_pos: 24 })
+        ));
+    }
+
+    #[test]
+    fn check_visitor_iter() {
+        let packet: Packet = "EE00D40C823060".parse().unwrap();
+        let visits = packet
+            .visitor_iter()
+            .map(|visit| (visit.packet.version, visit.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(visits, vec![(7, 0), (2, 1), (4, 1), (1, 1)]);
+        let packet: Packet = "8A004A801A8002F478".parse().unwrap();
+        let depths = packet
+            .visitor_iter()
+            .map(|visit| visit.depth)
+            .collect::<Vec<_>>();
+        assert_eq!(depths, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn check_derived_values_errors() {
+        let literal = |value| Packet {
+            version: 0,
+            packet_type: PacketType::Literal,
+            content: PacketContent::Literal(value),
+        };
+        let operator = |packet_type, sub_packets| Packet {
+            version: 0,
+            packet_type,
+            content: PacketContent::SubPackets(sub_packets),
+        };
+        assert_eq!(
+            derived_values(&operator(PacketType::GtProduct, vec![literal(1)])),
+            Err(EvalError::OperandCount {
+                packet_type: PacketType::GtProduct,
+                found: 1
+            })
+        );
+        assert_eq!(
+            derived_values(&operator(
+                PacketType::EqProduct,
+                vec![literal(1), literal(1), literal(1)]
+            )),
+            Err(EvalError::OperandCount {
+                packet_type: PacketType::EqProduct,
+                found: 3
+            })
+        );
+        assert_eq!(
+            derived_values(&operator(PacketType::MinProduct, vec![])),
+            Err(EvalError::OperandCount {
+                packet_type: PacketType::MinProduct,
+                found: 0
+            })
+        );
+        assert_eq!(
+            derived_values(&operator(PacketType::Literal, vec![literal(1)])),
+            Err(EvalError::TypeMismatch {
+                packet_type: PacketType::Literal
+            })
+        );
+        assert_eq!(
+            derived_values(&"0600848C210842108400246108421084200".parse().unwrap()),
+            Err(EvalError::Overflow {
+                packet_type: PacketType::Product
+            })
+        );
+        assert_eq!(
+            derived_values(&operator(
+                PacketType::Sum,
+                vec![literal(u64::MAX), literal(1)]
+            )),
+            Err(EvalError::Overflow {
+                packet_type: PacketType::Sum
+            })
+        );
+        let mismatch = Packet {
+            version: 0,
+            packet_type: PacketType::Sum,
+            content: PacketContent::Literal(1),
+        };
+        assert_eq!(
+            derived_values(&operator(PacketType::Product, vec![literal(2), mismatch])),
+            Err(EvalError::TypeMismatch {
+                packet_type: PacketType::Sum
+            })
+        );
+    }
+
+    #[test]
+    fn check_formula() {
+        for (hex, expected) in [
+            ("C200B40A82", "1 + 2"),
+            ("04005AC33890", "6 * 9"),
+            ("880086C3E88112", "min(7, 8, 9)"),
+            ("D8005AC2A8F0", "5 < 15"),
+            ("9C0141080250320F1802104A08", "1 + 3 == 2 * 2"),
+            ("D2FE28", "2021"),
+        ] {
+            assert_eq!(formula(&hex.parse().unwrap()), expected);
+        }
+        let literal = |value| Packet {
+            version: 0,
+            packet_type: PacketType::Literal,
+            content: PacketContent::Literal(value),
+        };
+        let operator = |packet_type, sub_packets| Packet {
+            version: 0,
+            packet_type,
+            content: PacketContent::SubPackets(sub_packets),
+        };
+        let sum = operator(PacketType::Sum, vec![literal(5), literal(2)]);
+        let product = operator(PacketType::Product, vec![sum, literal(9)]);
+        let max = operator(PacketType::MaxProduct, vec![literal(3), product]);
+        let comparison = operator(PacketType::GtProduct, vec![max, literal(4)]);
+        let nested = operator(
+            PacketType::Product,
+            vec![comparison, operator(PacketType::Sum, vec![literal(1)])],
+        );
+        assert_eq!(formula(&nested), "(max(3, (5 + 2) * 9) > 4) * 1");
+        assert_eq!(
+            formula(&operator(PacketType::GtProduct, vec![])),
+            "gtproduct()"
+        );
+    }
+
+    #[test]
+    fn check_to_json() {
+        assert_eq!(
+            to_json(1, &"9C0141080250320F1802104A08".parse()),
+            r#"{"line": 1, "summed_versions": 20, "value": 1, "error": null}"#
+        );
+        assert_eq!(
+            to_json(3, &"D2FE2G".parse()),
+            r#"{"line": 3, "summed_versions": null, "value": null, "error": "Encountered invalid hex char 'G' at 5"}"#
+        );
+    }
+
+    #[test]
+    fn check_dump() {
+        let (packet, spans) = parse_with_spans("38006F45291200").unwrap();
+        assert_eq!(
+            dump(&packet, &spans),
+            "LtProduct v1 (bits 0..49, 49 long)\n  \
+             Literal v6 = 10 (bits 22..33, 11 long)\n  \
+             Literal v2 = 20 (bits 33..49, 16 long)\n"
+        );
+    }
+
+    #[test]
+    fn check_encode_errors() {
+        let packet = Packet {
+            version: 8,
+            packet_type: PacketType::Literal,
+            content: PacketContent::Literal(0),
+        };
+        assert_eq!(packet.encode(), Err(EncodeError::VersionTooLarge(8)));
+        let packet = Packet {
+            version: 1,
+            packet_type: PacketType::Sum,
+            content: PacketContent::Literal(0),
+        };
+        assert_eq!(packet.encode(), Err(EncodeError::ContentMismatch));
+        assert!(matches!(
+            "38006F4529".parse::<Packet>(),
+            Err(PacketError::Truncated { .. })
+        ));
+    }
 }
@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Pos(pub i64, pub i64);
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Velocity(pub i64, pub i64);
+
+/// The target area, from its top left `start` to its bottom right `end`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ShotTarget {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TargetError {
+    /// The line doesn't start with `target area: `.
+    MissingPrefix,
+    /// An axis isn't given as `x=a..b` or `y=c..d`.
+    InvalidAxis(String),
+    /// A bound isn't a whole number.
+    InvalidNumber(String),
+}
+
+impl std::fmt::Display for TargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetError::MissingPrefix => write!(f, "Expected a line starting 'target area: '"),
+            TargetError::InvalidAxis(axis) => {
+                write!(f, "Expected a range like 'x=a..b', found '{}'", axis)
+            }
+            TargetError::InvalidNumber(number) => {
+                write!(f, "Expected a whole number, found '{}'", number)
+            }
+        }
+    }
+}
+
+/// Parses `name=a..b`, giving the bounds lowest first.
+fn parse_axis(axis: &str, name: &str) -> Result<(i64, i64), TargetError> {
+    let (a, b) = axis
+        .strip_prefix(name)
+        .and_then(|range| range.strip_prefix('='))
+        .and_then(|range| range.split_once(".."))
+        .ok_or_else(|| TargetError::InvalidAxis(axis.to_owned()))?;
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<i64>()
+            .map_err(|_| TargetError::InvalidNumber(n.to_owned()))
+    };
+    let (a, b) = (parse(a)?, parse(b)?);
+    Ok((a.min(b), a.max(b)))
+}
+
+impl FromStr for ShotTarget {
+    type Err = TargetError;
+
+    /// Parses `target area: x=a..b, y=c..d`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let area = s
+            .trim()
+            .strip_prefix("target area:")
+            .ok_or(TargetError::MissingPrefix)?;
+        let (x, y) = area
+            .split_once(',')
+            .ok_or_else(|| TargetError::InvalidAxis(area.trim().to_owned()))?;
+        let (x_min, x_max) = parse_axis(x.trim(), "x")?;
+        let (y_min, y_max) = parse_axis(y.trim(), "y")?;
+        Ok(ShotTarget {
+            start: Pos(x_min, y_max),
+            end: Pos(x_max, y_min),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TraceResult {
+    pub initial_velocity: Velocity,
+    pub positions: Vec<Pos>,
+    pub hit: bool,
+}
+
+impl TraceResult {
+    /// The highest the probe got.
+    pub fn max_y(&self) -> i64 {
+        self.positions.iter().map(|p| p.1).max().unwrap_or(0)
+    }
+
+    /// How many steps the probe took to reach the target, or to pass it.
+    pub fn steps(&self) -> usize {
+        self.positions.len() - 1
+    }
+}
+
+fn compare_to_target(pos: &Pos, shot_target: &ShotTarget) -> Ordering {
+    if pos.0 > shot_target.end.0 || pos.1 < shot_target.end.1 {
+        Ordering::Greater
+    } else if pos.0 >= shot_target.start.0 && pos.1 <= shot_target.start.1 {
+        Ordering::Equal
+    } else {
+        Ordering::Less
+    }
+}
+
+fn take_move(pos: &Pos, velocity: &Velocity) -> (Pos, Velocity) {
+    (
+        Pos(pos.0 + velocity.0, pos.1 + velocity.1),
+        Velocity(velocity.0 - velocity.0.signum(), velocity.1 - 1),
+    )
+}
+
+pub fn trace_velocity(initial_velocity: Velocity, shot_target: &ShotTarget) -> TraceResult {
+    let mut state = initial_velocity.clone();
+    let mut positions = vec![Pos(0, 0)];
+    let mut comp = Ordering::Less;
+    while comp == Ordering::Less {
+        let after_move = take_move(positions.last().unwrap(), &state);
+        comp = compare_to_target(&after_move.0, shot_target);
+        positions.push(after_move.0);
+        state = after_move.1;
+    }
+    TraceResult {
+        initial_velocity,
+        positions,
+        hit: comp == Ordering::Equal,
+    }
+}
+
+/// Every initial velocity that lands the probe in the target, traced.
+///
+/// No shot can hit with more speed across than the far side of the target is
+/// away, or more speed up or down than the furthest row is, as it comes back
+/// through y=0 as fast as it left.
+pub fn hitting_velocities(shot_target: &ShotTarget) -> impl Iterator<Item = TraceResult> + '_ {
+    let xs = shot_target.start.0.min(0)..=shot_target.end.0.max(0);
+    let ys = shot_target.end.1.min(0)..=shot_target.end.1.abs().max(shot_target.start.1.abs());
+    xs.flat_map(move |x| ys.clone().map(move |y| Velocity(x, y)))
+        .map(|velocity| trace_velocity(velocity, shot_target))
+        .filter(|t| t.hit)
+}
+
+/// The highest a hitting shot goes, worked out directly for a target below
+/// the launcher.
+///
+/// A shot comes back down through y=0 one faster than it went up, so the
+/// fastest that still lands falls from 0 to the bottom of the target in one
+/// step, having risen `-bottom - 1` and stopped at `bottom * (bottom + 1) / 2`.
+/// It reaches the bottom after `-2 * bottom` steps, so this only holds when
+/// some speed across has the probe over the target at that step.
+pub fn closed_form_max_y(shot_target: &ShotTarget) -> Option<i64> {
+    let bottom = shot_target.end.1;
+    let steps = -2 * bottom;
+    let x_after = |x: i64| {
+        if x <= steps {
+            x * (x + 1) / 2
+        } else {
+            steps * x - steps * (steps - 1) / 2
+        }
+    };
+    let over_target = (0..=shot_target.end.0)
+        .map(x_after)
+        .any(|x| (shot_target.start.0..=shot_target.end.0).contains(&x));
+    (shot_target.start.1 < 0 && over_target).then_some(bottom * (bottom + 1) / 2)
+}
+
+/// The highest any hitting shot goes, and how many shots hit.
+pub fn search_velocities(shot_target: &ShotTarget) -> (i64, usize) {
+    hitting_velocities(shot_target).fold((0, 0), |(max_y, count), t| {
+        (max_y.max(t.max_y()), count + 1)
+    })
+}
+
+/// How the hitting shots are spread across the number of steps they take,
+/// with the hitting shots that take the fewest and most steps and the one
+/// that goes highest, the first found where there's a tie.
+#[derive(Debug, Default)]
+pub struct ShotStats {
+    pub steps: BTreeMap<usize, usize>,
+    pub fastest: Option<TraceResult>,
+    pub slowest: Option<TraceResult>,
+    pub highest: Option<TraceResult>,
+}
+
+impl ShotStats {
+    pub fn new(hits: impl Iterator<Item = TraceResult>) -> Self {
+        hits.fold(ShotStats::default(), |mut stats, t| {
+            *stats.steps.entry(t.steps()).or_insert(0) += 1;
+            if stats.fastest.as_ref().is_none_or(|f| t.steps() < f.steps()) {
+                stats.fastest = Some(t.clone());
+            }
+            if stats.slowest.as_ref().is_none_or(|s| t.steps() > s.steps()) {
+                stats.slowest = Some(t.clone());
+            }
+            if stats.highest.as_ref().is_none_or(|h| t.max_y() > h.max_y()) {
+                stats.highest = Some(t);
+            }
+            stats
+        })
+    }
+
+    /// How many shots hit.
+    pub fn count(&self) -> usize {
+        self.steps.values().sum()
+    }
+}
+
+impl std::fmt::Display for ShotStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Steps to hit:")?;
+        for (steps, count) in &self.steps {
+            writeln!(f, "  {}: {}", steps, count)?;
+        }
+        let ties = |steps| self.steps.get(&steps).copied().unwrap_or(0);
+        if let Some(t) = &self.fastest {
+            writeln!(
+                f,
+                "Fastest: {:?} in {} steps, one of {}",
+                t.initial_velocity,
+                t.steps(),
+                ties(t.steps())
+            )?;
+        }
+        if let Some(t) = &self.slowest {
+            writeln!(
+                f,
+                "Slowest: {:?} in {} steps, one of {}",
+                t.initial_velocity,
+                t.steps(),
+                ties(t.steps())
+            )?;
+        }
+        if let Some(t) = &self.highest {
+            writeln!(
+                f,
+                "Highest: {:?} reaching y={}",
+                t.initial_velocity,
+                t.max_y()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse_target() {
+        assert_eq!(
+            "target area: x=20..30, y=-10..-5".parse(),
+            Ok(ShotTarget {
+                start: Pos(20, -5),
+                end: Pos(30, -10),
+            })
+        );
+        assert_eq!(
+            "target area: x=30..20, y=-5..-10\n".parse(),
+            Ok(ShotTarget {
+                start: Pos(20, -5),
+                end: Pos(30, -10),
+            })
+        );
+        assert_eq!(
+            "area: x=20..30, y=-10..-5".parse::<ShotTarget>(),
+            Err(TargetError::MissingPrefix)
+        );
+        assert_eq!(
+            "target area: x=20..30".parse::<ShotTarget>(),
+            Err(TargetError::InvalidAxis("x=20..30".to_owned()))
+        );
+        assert_eq!(
+            "target area: y=20..30, x=-10..-5".parse::<ShotTarget>(),
+            Err(TargetError::InvalidAxis("y=20..30".to_owned()))
+        );
+        assert_eq!(
+            "target area: x=20..3a, y=-10..-5".parse::<ShotTarget>(),
+            Err(TargetError::InvalidNumber("3a".to_owned()))
+        );
+    }
+
+    #[test]
+    fn check_hitting_velocities() {
+        let shot_target = ShotTarget {
+            start: Pos(20, -5),
+            end: Pos(30, -10),
+        };
+        let hits = hitting_velocities(&shot_target).collect::<Vec<_>>();
+        assert_eq!(hits.len(), 112);
+        assert!(hits.iter().all(|t| t.hit));
+        assert!(hits.iter().any(|t| t.initial_velocity == Velocity(6, 9)));
+        let highest = hits.iter().max_by_key(|t| t.max_y()).unwrap();
+        assert_eq!(highest.max_y(), 45);
+        assert_eq!(highest.steps(), 20);
+        let fastest = hits.iter().min_by_key(|t| t.steps()).unwrap();
+        assert_eq!(fastest.steps(), 1);
+    }
+
+    #[test]
+    fn check_shot_stats() {
+        let stats = ShotStats::new(hitting_velocities(&ShotTarget {
+            start: Pos(20, -5),
+            end: Pos(30, -10),
+        }));
+        assert_eq!(stats.count(), 112);
+        assert_eq!(stats.steps.get(&1), Some(&66));
+        let fastest = stats.fastest.unwrap();
+        assert_eq!(fastest.initial_velocity, Velocity(20, -10));
+        let slowest = stats.slowest.unwrap();
+        assert_eq!(slowest.steps(), 20);
+        let highest = stats.highest.unwrap();
+        assert_eq!(highest.initial_velocity, Velocity(6, 9));
+        assert_eq!(highest.max_y(), 45);
+    }
+
+    #[test]
+    fn check_hitting_velocities_beyond_old_window() {
+        let far = ShotTarget {
+            start: Pos(700, -5),
+            end: Pos(800, -10),
+        };
+        assert_eq!(hitting_velocities(&far).count(), 998);
+        let deep = ShotTarget {
+            start: Pos(20, -990),
+            end: Pos(30, -1000),
+        };
+        assert_eq!(search_velocities(&deep).0, 499500);
+    }
+
+    #[test]
+    fn check_closed_form_max_y() {
+        let shot_target = ShotTarget {
+            start: Pos(20, -5),
+            end: Pos(30, -10),
+        };
+        assert_eq!(closed_form_max_y(&shot_target), Some(45));
+        assert_eq!(
+            closed_form_max_y(&ShotTarget {
+                start: Pos(195, -67),
+                end: Pos(238, -93),
+            }),
+            Some(4278)
+        );
+        // Above the launcher, or too narrow to stop over.
+        assert_eq!(
+            closed_form_max_y(&ShotTarget {
+                start: Pos(20, 10),
+                end: Pos(30, 5),
+            }),
+            None
+        );
+        assert_eq!(
+            closed_form_max_y(&ShotTarget {
+                start: Pos(22, -5),
+                end: Pos(27, -10),
+            }),
+            None
+        );
+        // Too far to stop over, and passed over by the fastest drop's step.
+        let narrow = ShotTarget {
+            start: Pos(55, -2),
+            end: Pos(55, -2),
+        };
+        assert_eq!(closed_form_max_y(&narrow), None);
+        assert_eq!(search_velocities(&narrow).0, 0);
+    }
+
+    #[test]
+
+    fn check_search_velocities() {
+        let (max_y, hit_count) = search_velocities(&ShotTarget {
+            start: Pos(20, -5),
+            end: Pos(30, -10),
+        });
+        assert_eq!(max_y, 45);
+        assert_eq!(hit_count, 112);
+    }
+}
@@ -4,6 +4,7 @@ use std::{
     ops::Add,
     ptr,
     str::FromStr,
+    time::Instant,
 };
 
 #[derive(Debug, PartialEq)]
@@ -67,7 +68,7 @@ impl Node {
         }
     }
 
-    fn visitor_iter(&self) -> NodeVisitor {
+    fn visitor_iter(&self) -> NodeVisitor<'_> {
         NodeVisitor {
             stack: vec![NodeVisit {
                 node: self,
@@ -76,7 +77,77 @@ impl Node {
         }
     }
 
-    fn reduce(self) -> Self {
+    /// Reduces the number in place, exploding every pair it can before each
+    /// split of the leftmost value over 9.
+    fn reduce(mut self) -> Self {
+        while self.explode() || self.split() {}
+        self
+    }
+
+    /// Explodes the leftmost pair nested inside four others, if there is one.
+    fn explode(&mut self) -> bool {
+        self.explode_at(0).is_some()
+    }
+
+    /// Explodes the leftmost pair nested deep enough below this one, giving
+    /// back what's still to be added to the values either side of it.
+    fn explode_at(&mut self, depth: usize) -> Option<(Option<u8>, Option<u8>)> {
+        let NodeKind::Container(n1, n2) = &mut self.kind else {
+            return None;
+        };
+        if depth > 3 {
+            if let (NodeKind::Value(val1), NodeKind::Value(val2)) = (&n1.kind, &n2.kind) {
+                let carry = (Some(*val1), Some(*val2));
+                *self = Node::value_of(0);
+                return Some(carry);
+            }
+        }
+        if let Some((val1, val2)) = n1.explode_at(depth + 1) {
+            if let Some(val2) = val2 {
+                n2.add_to_leftmost(val2);
+            }
+            return Some((val1, None));
+        }
+        let (val1, val2) = n2.explode_at(depth + 1)?;
+        if let Some(val1) = val1 {
+            n1.add_to_rightmost(val1);
+        }
+        Some((None, val2))
+    }
+
+    fn add_to_leftmost(&mut self, value: u8) {
+        match &mut self.kind {
+            NodeKind::Container(n1, _) => n1.add_to_leftmost(value),
+            NodeKind::Value(v) => *v += value,
+        }
+    }
+
+    fn add_to_rightmost(&mut self, value: u8) {
+        match &mut self.kind {
+            NodeKind::Container(_, n2) => n2.add_to_rightmost(value),
+            NodeKind::Value(v) => *v += value,
+        }
+    }
+
+    /// Splits the leftmost value over 9 into a pair, if there is one.
+    fn split(&mut self) -> bool {
+        match &mut self.kind {
+            NodeKind::Container(n1, n2) => n1.split() || n2.split(),
+            NodeKind::Value(v) if *v > 9 => {
+                let v = *v;
+                *self = Node::containing(
+                    Node::value_of(v.div_euclid(2)),
+                    Node::value_of(v.add(1).div_euclid(2)),
+                );
+                true
+            }
+            NodeKind::Value(_) => false,
+        }
+    }
+
+    /// Reduces the number by rebuilding the whole tree for every explosion and
+    /// split, as it first was, to benchmark against.
+    fn reduce_by_rebuilding(self) -> Self {
         let mut tree = self;
         let mut no_updates = false;
 
@@ -158,7 +229,7 @@ impl Node {
                 n1.apply_explosion(explode_visit, previous_visit, next_visit, val1, val2),
                 n2.apply_explosion(explode_visit, previous_visit, next_visit, val1, val2),
             ),
-            NodeKind::Value(v) => Node::value_of(v.clone()),
+            NodeKind::Value(v) => Node::value_of(*v),
         }
     }
 
@@ -181,17 +252,27 @@ impl Node {
             }
             NodeKind::Value(v) => {
                 if ptr::eq(self, split_visit.node) {
-                    return Node::containing(
+                    Node::containing(
                         Node::value_of(v.div_euclid(2)),
                         Node::value_of(v.add(1).div_euclid(2)),
-                    );
+                    )
                 } else {
-                    Node::value_of(v.clone())
+                    Node::value_of(*v)
                 }
             }
         }
     }
 }
+impl std::fmt::Display for Node {
+    /// Writes the number in its bracket notation, such as `[[1,2],3]`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            NodeKind::Container(n1, n2) => write!(f, "[{},{}]", n1, n2),
+            NodeKind::Value(v) => write!(f, "{}", v),
+        }
+    }
+}
+
 impl Add for Node {
     type Output = Self;
 
@@ -209,13 +290,13 @@ impl FromStr for Node {
 }
 
 fn parse_tree(s: &str) -> Result<(Node, usize), &'static str> {
-    let first_char = s.chars().nth(0).ok_or("Empty")?;
+    let first_char = s.chars().next().ok_or("Empty")?;
     let first_char = first_char as u8;
     match first_char {
         b'0'..=b'9' => {
             let value = first_char - b'0';
             let next_char = s.chars().nth(1).unwrap_or(']') as u8;
-            if matches!(next_char, b'0'..=b'9') {
+            if next_char.is_ascii_digit() {
                 Ok((Node::value_of(value * 10 + (next_char - b'0')), 2))
             } else {
                 Ok((Node::value_of(value), 1))
@@ -230,24 +311,54 @@ fn parse_tree(s: &str) -> Result<(Node, usize), &'static str> {
     }
 }
 
-fn main() {
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
     let lines: Vec<String> = BufReader::new(std::io::stdin())
         .lines()
-        .filter_map(|f| f.ok())
+        .map_while(Result::ok)
         .collect();
 
+    let trace = args.iter().any(|a| a == "--trace");
     let total_result = lines
         .clone()
         .iter()
         .filter_map(|s| s.parse::<Node>().ok())
-        .fold(None, |current, next_node| match current {
-            Some(prev_node) => Some(prev_node + next_node),
-            None => Some(next_node),
+        .fold(None, |current, next_node| {
+            let total = match current {
+                Some(prev_node) => prev_node + next_node,
+                None => next_node,
+            };
+            if trace {
+                println!("{}", total);
+            }
+            Some(total)
         })
-        .unwrap();
+        .ok_or("Expected at least one snailfish number")?;
 
     println!("Total magnitude: {}", total_result.magnitude());
 
+    if args.iter().any(|a| a == "--benchmark") {
+        let nodes = || lines.iter().filter_map(|s| s.parse::<Node>().ok());
+        let started = Instant::now();
+        let in_place = nodes().reduce(|n1, n2| n1 + n2);
+        let in_place_time = started.elapsed();
+        let started = Instant::now();
+        let rebuilt = nodes().reduce(|n1, n2| Node::containing(n1, n2).reduce_by_rebuilding());
+        let rebuilt_time = started.elapsed();
+        if let (Some(in_place), Some(rebuilt)) = (&in_place, &rebuilt) {
+            if in_place != rebuilt {
+                return Err(format!(
+                    "The reductions disagree on the total, {} in place but {} rebuilding",
+                    in_place, rebuilt
+                ));
+            }
+        }
+        println!(
+            "In place took {:?}, rebuilding took {:?} for the total",
+            in_place_time, rebuilt_time
+        );
+    }
+
     let mut best_magnitude = 0;
 
     for line in lines.iter() {
@@ -263,6 +374,7 @@ fn main() {
         }
     }
     println!("best_magnitude: {}", best_magnitude);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -604,6 +716,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_display_round_trip() {
+        for number in [
+            "[1,2]",
+            "[[1,2],3]",
+            "[[[[0,7],4],[15,[0,13]]],[1,1]]",
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]",
+        ] {
+            assert_eq!(number.parse::<Node>().unwrap().to_string(), number);
+        }
+        assert_eq!(
+            Node::containing(Node::value_of(10), Node::value_of(3)).to_string(),
+            "[10,3]"
+        );
+    }
+
+    #[test]
+    fn check_explode_in_place() {
+        for (before, after) in [
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            (
+                "[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]",
+                "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]",
+            ),
+        ] {
+            let mut node = before.parse::<Node>().unwrap();
+            assert!(node.explode());
+            assert_eq!(node, after.parse::<Node>().unwrap());
+        }
+        let mut node = "[[1,2],[[1,2],3]]".parse::<Node>().unwrap();
+        assert!(!node.explode());
+    }
+
+    #[test]
+    fn check_split_in_place() {
+        let mut node = "[[[[0,7],4],[15,[0,13]]],[1,1]]".parse::<Node>().unwrap();
+        assert!(node.split());
+        assert_eq!(
+            node,
+            "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]"
+                .parse::<Node>()
+                .unwrap()
+        );
+        assert!(node.split());
+        assert_eq!(
+            node,
+            "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]"
+                .parse::<Node>()
+                .unwrap()
+        );
+        assert!(!node.split());
+    }
+
+    #[test]
+    fn check_reduce_by_rebuilding() {
+        assert_eq!(
+            Node::containing(
+                "[[[[4,3],4],4],[7,[[8,4],9]]]".parse::<Node>().unwrap(),
+                "[1,1]".parse::<Node>().unwrap()
+            )
+            .reduce_by_rebuilding(),
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]".parse::<Node>().unwrap()
+        );
+    }
+
     #[test]
     fn check_add_simple() {
         assert_eq!(
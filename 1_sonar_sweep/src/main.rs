@@ -1,23 +1,78 @@
 use std::io::{BufRead, BufReader};
 
-fn main() {
+fn main() -> Result<(), String> {
     let reader = BufReader::new(std::io::stdin());
     let args: Vec<String> = std::env::args().collect();
-
-    let count = match args.get(1) {
-        Some(v) if v == "windowed" => {
-            count_depth_increments(reader, WindowedResultDepthMeasure::new())
-        }
-        _ => count_depth_increments(reader, SingleResultDepthMeasure::new()),
+    let parsing = if args.iter().any(|a| a == "--lenient") {
+        Parsing::Lenient
+    } else {
+        Parsing::Strict
     };
+
+    let count = if args.iter().any(|a| a == "windowed") {
+        count_depth_increments(reader, WindowedResultDepthMeasure::new(), parsing)
+    } else {
+        count_depth_increments(reader, SingleResultDepthMeasure::new(), parsing)
+    }
+    .map_err(|err| err.to_string())?;
     println!("Count {}", count);
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Parsing {
+    /// Any line that is not an integer aborts the count.
+    Strict,
+    /// Lines that are not integers are skipped.
+    Lenient,
 }
 
-fn count_depth_increments(read: impl std::io::BufRead, from: impl DepthMeasure) -> i32 {
-    let readings = BufReader::new(read)
-        .lines()
-        .filter_map(Result::ok)
-        .filter_map(|s| s.parse::<i32>().ok());
+#[derive(Debug, PartialEq)]
+enum ReadingError {
+    Malformed { line_number: usize, content: String },
+    Io(String),
+}
+
+impl std::fmt::Display for ReadingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadingError::Malformed {
+                line_number,
+                content,
+            } => write!(
+                f,
+                "Line {} is not a depth reading: {:?}",
+                line_number, content
+            ),
+            ReadingError::Io(err) => write!(f, "Unable to read input: {}", err),
+        }
+    }
+}
+
+fn extract_readings(read: impl BufRead, parsing: Parsing) -> Result<Vec<i32>, ReadingError> {
+    let mut readings = Vec::new();
+    for (idx, line) in read.lines().enumerate() {
+        let line = line.map_err(|err| ReadingError::Io(err.to_string()))?;
+        match line.trim().parse::<i32>() {
+            Ok(reading) => readings.push(reading),
+            Err(_) if parsing == Parsing::Lenient => {}
+            Err(_) => {
+                return Err(ReadingError::Malformed {
+                    line_number: idx + 1,
+                    content: line,
+                })
+            }
+        }
+    }
+    Ok(readings)
+}
+
+fn count_depth_increments(
+    read: impl BufRead,
+    from: impl DepthMeasure,
+    parsing: Parsing,
+) -> Result<i32, ReadingError> {
+    let readings = extract_readings(read, parsing)?;
 
     let mut counter = 0;
     let mut previous = from;
@@ -30,7 +85,7 @@ fn count_depth_increments(read: impl std::io::BufRead, from: impl DepthMeasure)
         }
         previous = measure
     }
-    return counter;
+    Ok(counter)
 }
 
 trait DepthMeasure {
@@ -96,13 +151,35 @@ mod tests {
     #[test]
     fn can_count_changes() {
         let cursor = Cursor::new(SIMPLE_CASE);
-        let count = count_depth_increments(cursor, SingleResultDepthMeasure::new());
-        assert_eq!(count, 7);
+        let count =
+            count_depth_increments(cursor, SingleResultDepthMeasure::new(), Parsing::Strict);
+        assert_eq!(count, Ok(7));
     }
     #[test]
     fn can_count_changes_with_window() {
         let cursor = Cursor::new(SIMPLE_CASE);
-        let count = count_depth_increments(cursor, WindowedResultDepthMeasure::new());
-        assert_eq!(count, 5);
+        let count =
+            count_depth_increments(cursor, WindowedResultDepthMeasure::new(), Parsing::Strict);
+        assert_eq!(count, Ok(5));
+    }
+    #[test]
+    fn strict_parsing_reports_malformed_line() {
+        let cursor = Cursor::new(b"199\n200\n2O8\n210".as_slice());
+        let count =
+            count_depth_increments(cursor, SingleResultDepthMeasure::new(), Parsing::Strict);
+        assert_eq!(
+            count,
+            Err(ReadingError::Malformed {
+                line_number: 3,
+                content: "2O8".to_owned()
+            })
+        );
+    }
+    #[test]
+    fn lenient_parsing_skips_malformed_line() {
+        let cursor = Cursor::new(b"199\n200\n2O8\n210".as_slice());
+        let count =
+            count_depth_increments(cursor, SingleResultDepthMeasure::new(), Parsing::Lenient);
+        assert_eq!(count, Ok(2));
     }
 }
@@ -0,0 +1,669 @@
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Movement {
+    Up(i32),
+    Down(i32),
+    Forward(i32),
+    Hold,
+    Surface,
+}
+
+/// The most times a single line can repeat its command.
+pub const MAX_REPEAT: usize = 10_000;
+
+/// Parses a single command line, e.g. `forward 5`, `surface` or `down 2 x3`.
+///
+/// Anything after a `#` is a comment, so blank and comment-only lines yield no
+/// movements. A trailing `xN` repeats the command `N` times, up to
+/// [`MAX_REPEAT`].
+pub fn extract_reading(line: String) -> Option<Vec<Movement>> {
+    let command = line.split('#').next().unwrap_or_default();
+    let mut segments = command.split_whitespace().collect::<Vec<&str>>();
+
+    let repeat = match segments.last().and_then(|s| s.strip_prefix('x')) {
+        Some(count) if segments.len() > 1 => {
+            segments.pop();
+            count.parse::<usize>().ok().filter(|n| *n <= MAX_REPEAT)?
+        }
+        _ => 1,
+    };
+    let distance = segments.get(1).map(|d| d.parse::<i32>().ok());
+
+    let movement = match (segments.first(), distance, segments.len()) {
+        (None, _, _) => return Some(Vec::new()),
+        (Some(&"forward"), Some(Some(d)), 2) => Movement::Forward(d),
+        (Some(&"up"), Some(Some(d)), 2) => Movement::Up(d),
+        (Some(&"down"), Some(Some(d)), 2) => Movement::Down(d),
+        (Some(&"hold"), None, 1) => Movement::Hold,
+        (Some(&"surface"), None, 1) => Movement::Surface,
+        _ => return None,
+    };
+    Some(vec![movement; repeat])
+}
+
+/// The state of the submarine shared by every strategy. Strategies that have
+/// no use for `aim` or `waypoint` leave them untouched.
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub struct Position {
+    pub depth: i32,
+    pub horizontal: i32,
+    pub aim: i32,
+    pub waypoint: Waypoint,
+}
+
+/// A point relative to the submarine.
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub struct Waypoint {
+    pub depth: i32,
+    pub horizontal: i32,
+}
+
+/// An interpretation of what each movement does to the submarine.
+pub trait DiveStrategy {
+    fn name(&self) -> &str;
+    fn steer(&self, position: Position, movement: Movement) -> Position;
+
+    fn start(&self) -> Position {
+        Position::default()
+    }
+
+    /// The names of the columns this strategy adds to a trace.
+    fn trace_columns(&self) -> Vec<&str> {
+        vec!["depth", "horizontal", "aim"]
+    }
+
+    /// The values of the columns named by [`DiveStrategy::trace_columns`].
+    fn trace_values(&self, position: Position) -> Vec<i32> {
+        vec![position.depth, position.horizontal, position.aim]
+    }
+}
+
+pub struct SimpleDive;
+
+impl DiveStrategy for SimpleDive {
+    fn name(&self) -> &str {
+        "Simple"
+    }
+
+    fn steer(&self, position: Position, movement: Movement) -> Position {
+        let Position {
+            depth, horizontal, ..
+        } = position;
+        match movement {
+            Movement::Up(d) => Position {
+                depth: depth - d,
+                ..position
+            },
+            Movement::Down(d) => Position {
+                depth: depth + d,
+                ..position
+            },
+            Movement::Forward(d) => Position {
+                horizontal: horizontal + d,
+                ..position
+            },
+            Movement::Hold => position,
+            Movement::Surface => Position {
+                depth: 0,
+                ..position
+            },
+        }
+    }
+}
+
+pub struct AimedDive;
+
+impl DiveStrategy for AimedDive {
+    fn name(&self) -> &str {
+        "Aimed"
+    }
+
+    fn steer(&self, position: Position, movement: Movement) -> Position {
+        let Position {
+            depth,
+            horizontal,
+            aim,
+            ..
+        } = position;
+        match movement {
+            Movement::Up(d) => Position {
+                aim: aim - d,
+                ..position
+            },
+            Movement::Down(d) => Position {
+                aim: aim + d,
+                ..position
+            },
+            Movement::Forward(d) => Position {
+                depth: depth + (d * aim),
+                horizontal: horizontal + d,
+                ..position
+            },
+            Movement::Hold => position,
+            Movement::Surface => Position {
+                depth: 0,
+                aim: 0,
+                ..position
+            },
+        }
+    }
+}
+
+/// Up and down move a waypoint relative to the submarine, while forward moves
+/// the submarine to the waypoint that many times. The waypoint starts 10
+/// ahead and 1 below the submarine.
+pub struct WaypointDive;
+
+impl DiveStrategy for WaypointDive {
+    fn name(&self) -> &str {
+        "Waypoint"
+    }
+
+    fn start(&self) -> Position {
+        Position {
+            waypoint: Waypoint {
+                depth: 1,
+                horizontal: 10,
+            },
+            ..Position::default()
+        }
+    }
+
+    fn trace_columns(&self) -> Vec<&str> {
+        vec![
+            "depth",
+            "horizontal",
+            "waypoint depth",
+            "waypoint horizontal",
+        ]
+    }
+
+    fn trace_values(&self, position: Position) -> Vec<i32> {
+        vec![
+            position.depth,
+            position.horizontal,
+            position.waypoint.depth,
+            position.waypoint.horizontal,
+        ]
+    }
+
+    fn steer(&self, position: Position, movement: Movement) -> Position {
+        let Position {
+            depth,
+            horizontal,
+            waypoint,
+            ..
+        } = position;
+        match movement {
+            Movement::Up(d) => Position {
+                waypoint: Waypoint {
+                    depth: waypoint.depth - d,
+                    ..waypoint
+                },
+                ..position
+            },
+            Movement::Down(d) => Position {
+                waypoint: Waypoint {
+                    depth: waypoint.depth + d,
+                    ..waypoint
+                },
+                ..position
+            },
+            Movement::Forward(d) => Position {
+                depth: depth + (d * waypoint.depth),
+                horizontal: horizontal + (d * waypoint.horizontal),
+                ..position
+            },
+            Movement::Hold => position,
+            Movement::Surface => Position {
+                depth: 0,
+                waypoint: Waypoint {
+                    depth: 0,
+                    ..waypoint
+                },
+                ..position
+            },
+        }
+    }
+}
+
+/// Follows a course under several strategies at once.
+pub struct Course {
+    legs: Vec<(Box<dyn DiveStrategy>, Position)>,
+}
+
+impl Course {
+    pub fn new(strategies: Vec<Box<dyn DiveStrategy>>) -> Self {
+        Course {
+            legs: strategies
+                .into_iter()
+                .map(|strategy| {
+                    let position = strategy.start();
+                    (strategy, position)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn steer(self, movement: Movement) -> Self {
+        Course {
+            legs: self
+                .legs
+                .into_iter()
+                .map(|(strategy, position)| {
+                    let position = strategy.steer(position, movement);
+                    (strategy, position)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (&str, Position)> {
+        self.legs
+            .iter()
+            .map(|(strategy, position)| (strategy.name(), *position))
+    }
+
+    /// Tab separated column names matching [`Course::trace_line`].
+    pub fn trace_header(&self) -> String {
+        self.legs
+            .iter()
+            .flat_map(|(strategy, _)| {
+                strategy
+                    .trace_columns()
+                    .into_iter()
+                    .map(|column| format!("\t{} {}", strategy.name(), column))
+            })
+            .fold("Movement".to_owned(), |line, column| line + &column)
+    }
+
+    /// A tab separated row of the position under every strategy, for printing
+    /// after each movement.
+    pub fn trace_line(&self, movement: Movement) -> String {
+        self.legs
+            .iter()
+            .flat_map(|(strategy, position)| strategy.trace_values(*position))
+            .fold(format!("{:?}", movement), |line, value| {
+                line + &format!("\t{}", value)
+            })
+    }
+}
+
+impl Default for Course {
+    fn default() -> Self {
+        Course::new(vec![Box::new(SimpleDive), Box::new(AimedDive)])
+    }
+}
+
+impl FromIterator<Movement> for Course {
+    fn from_iter<I: IntoIterator<Item = Movement>>(iter: I) -> Self {
+        iter.into_iter().fold(Course::default(), Course::steer)
+    }
+}
+
+/// Renders trajectories as ASCII art with the horizontal position running
+/// across and depth running down, all sharing the same axes. Each trajectory
+/// is drawn with the first letter of its name; later ones are drawn on top.
+pub fn plot(trajectories: &[(&str, Vec<Position>)], width: usize, height: usize) -> String {
+    let points = trajectories.iter().flat_map(|(_, path)| path.iter());
+    let (min_h, max_h, min_d, max_d) = points.fold((0, 0, 0, 0), |(a, b, c, d), p| {
+        (
+            a.min(p.horizontal),
+            b.max(p.horizontal),
+            c.min(p.depth),
+            d.max(p.depth),
+        )
+    });
+    let scale = |value: i32, min: i32, max: i32, cells: usize| -> i64 {
+        let span = i64::from(max) - i64::from(min);
+        if span == 0 || cells < 2 {
+            0
+        } else {
+            (i64::from(value) - i64::from(min)) * (cells as i64 - 1) / span
+        }
+    };
+
+    let mut grid = vec![vec![' '; width]; height];
+    for (name, path) in trajectories {
+        let glyph = name.chars().next().unwrap_or('*');
+        let cells = path
+            .iter()
+            .map(|p| {
+                (
+                    scale(p.horizontal, min_h, max_h, width),
+                    scale(p.depth, min_d, max_d, height),
+                )
+            })
+            .collect::<Vec<_>>();
+        for (from, to) in cells.iter().zip(cells.iter().skip(1).chain(cells.last())) {
+            let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+            for step in 0..=steps {
+                let col = from.0 + (to.0 - from.0) * step / steps;
+                let row = from.1 + (to.1 - from.1) * step / steps;
+                if let Some(cell) = grid
+                    .get_mut(row as usize)
+                    .and_then(|r| r.get_mut(col as usize))
+                {
+                    *cell = glyph;
+                }
+            }
+        }
+    }
+
+    let mut output = format!(
+        "Horizontal {}..{} across, depth {}..{} down\n",
+        min_h, max_h, min_d, max_d
+    );
+    for row in grid {
+        output.push('|');
+        output.extend(row);
+        output.push_str("|\n");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_extract_readings() {
+        assert_eq!(
+            extract_reading("forward 5".to_owned()),
+            Some(vec![Movement::Forward(5)])
+        );
+        assert_eq!(
+            extract_reading("down 5".to_owned()),
+            Some(vec![Movement::Down(5)])
+        );
+        assert_eq!(
+            extract_reading("forward 8".to_owned()),
+            Some(vec![Movement::Forward(8)])
+        );
+        assert_eq!(
+            extract_reading("up 3".to_owned()),
+            Some(vec![Movement::Up(3)])
+        );
+        assert_eq!(extract_reading("forward a".to_owned()), None);
+        assert_eq!(extract_reading("upwards 5".to_owned()), None);
+        assert_eq!(extract_reading("reset".to_owned()), None);
+    }
+    #[test]
+    fn check_extract_extended_readings() {
+        assert_eq!(
+            extract_reading("forward 5 x3".to_owned()),
+            Some(vec![Movement::Forward(5); 3])
+        );
+        assert_eq!(
+            extract_reading("down 2 # steady descent".to_owned()),
+            Some(vec![Movement::Down(2)])
+        );
+        assert_eq!(extract_reading("# comment".to_owned()), Some(vec![]));
+        assert_eq!(extract_reading("".to_owned()), Some(vec![]));
+        assert_eq!(
+            extract_reading("hold".to_owned()),
+            Some(vec![Movement::Hold])
+        );
+        assert_eq!(
+            extract_reading("surface x2".to_owned()),
+            Some(vec![Movement::Surface; 2])
+        );
+        assert_eq!(
+            extract_reading("up 0".to_owned()),
+            Some(vec![Movement::Up(0)])
+        );
+        assert_eq!(extract_reading("forward 5 x0".to_owned()), Some(vec![]));
+        assert_eq!(extract_reading("forward 5 xa".to_owned()), None);
+        assert_eq!(
+            extract_reading("forward 1 x10000".to_owned()).map(|m| m.len()),
+            Some(MAX_REPEAT)
+        );
+        assert_eq!(extract_reading("forward 1 x10001".to_owned()), None);
+        assert_eq!(
+            extract_reading("forward 1 x99999999999999999".to_owned()),
+            None
+        );
+        assert_eq!(extract_reading("forward 5 6".to_owned()), None);
+        assert_eq!(extract_reading("hold 5".to_owned()), None);
+        assert_eq!(extract_reading("x3".to_owned()), None);
+    }
+    #[test]
+    fn check_hold_and_surface() {
+        let position = Position {
+            depth: 10,
+            horizontal: 3,
+            aim: 4,
+            waypoint: Waypoint {
+                depth: 2,
+                horizontal: 5,
+            },
+        };
+        assert_eq!(SimpleDive.steer(position, Movement::Hold), position);
+        assert_eq!(
+            SimpleDive.steer(position, Movement::Surface),
+            Position {
+                depth: 0,
+                ..position
+            }
+        );
+        assert_eq!(AimedDive.steer(position, Movement::Hold), position);
+        assert_eq!(
+            AimedDive.steer(position, Movement::Surface),
+            Position {
+                depth: 0,
+                aim: 0,
+                ..position
+            }
+        );
+    }
+    #[test]
+    fn check_dive() {
+        let movements = vec![
+            Movement::Forward(5),
+            Movement::Down(5),
+            Movement::Forward(8),
+            Movement::Up(3),
+            Movement::Down(8),
+            Movement::Forward(2),
+        ];
+
+        let results = movements.into_iter().fold(Vec::new(), |mut r, movement| {
+            let last = r.last().copied().unwrap_or_default();
+            r.push(SimpleDive.steer(last, movement));
+            r
+        });
+
+        let expectation = vec![(0, 5), (5, 5), (5, 13), (2, 13), (10, 13), (10, 15)];
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|p| (p.depth, p.horizontal))
+                .collect::<Vec<_>>(),
+            expectation
+        );
+    }
+    #[test]
+    fn check_aimed_dive() {
+        let movements = vec![
+            Movement::Forward(5),
+            Movement::Down(5),
+            Movement::Forward(8),
+            Movement::Up(3),
+            Movement::Down(8),
+            Movement::Forward(2),
+        ];
+
+        let results = movements.into_iter().fold(Vec::new(), |mut r, movement| {
+            let last = r.last().copied().unwrap_or_default();
+            r.push(AimedDive.steer(last, movement));
+            r
+        });
+
+        let expectation = vec![
+            (0, 5, 0),
+            (0, 5, 5),
+            (40, 13, 5),
+            (40, 13, 2),
+            (40, 13, 10),
+            (60, 15, 10),
+        ];
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|p| (p.depth, p.horizontal, p.aim))
+                .collect::<Vec<_>>(),
+            expectation
+        );
+    }
+    #[test]
+    fn check_waypoint_dive() {
+        let movements = vec![
+            Movement::Forward(5),
+            Movement::Down(5),
+            Movement::Forward(8),
+            Movement::Up(3),
+            Movement::Down(8),
+            Movement::Forward(2),
+            Movement::Surface,
+        ];
+
+        let results = movements
+            .into_iter()
+            .fold(vec![WaypointDive.start()], |mut r, movement| {
+                let last = *r.last().unwrap();
+                r.push(WaypointDive.steer(last, movement));
+                r
+            });
+
+        let expectation = vec![
+            (0, 0, 1),
+            (5, 50, 1),
+            (5, 50, 6),
+            (53, 130, 6),
+            (53, 130, 3),
+            (53, 130, 11),
+            (75, 150, 11),
+            (0, 150, 0),
+        ];
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|p| (p.depth, p.horizontal, p.waypoint.depth))
+                .collect::<Vec<_>>(),
+            expectation
+        );
+    }
+    #[test]
+    fn check_course() {
+        let course: Course = vec![
+            Movement::Forward(5),
+            Movement::Down(5),
+            Movement::Forward(8),
+            Movement::Up(3),
+            Movement::Down(8),
+            Movement::Forward(2),
+        ]
+        .into_iter()
+        .collect();
+
+        let positions = course
+            .positions()
+            .map(|(name, p)| (name, p.depth, p.horizontal))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![("Simple", 10, 15), ("Aimed", 60, 15)]);
+    }
+    #[test]
+    fn check_course_with_custom_strategy() {
+        struct Sinking;
+        impl DiveStrategy for Sinking {
+            fn name(&self) -> &str {
+                "Sinking"
+            }
+            fn steer(&self, position: Position, movement: Movement) -> Position {
+                let position = SimpleDive.steer(position, movement);
+                Position {
+                    depth: position.depth + 1,
+                    ..position
+                }
+            }
+        }
+
+        let course = Course::new(vec![Box::new(Sinking)])
+            .steer(Movement::Forward(5))
+            .steer(Movement::Hold);
+        assert_eq!(
+            course.trace_header(),
+            "Movement\tSinking depth\tSinking horizontal\tSinking aim"
+        );
+        assert_eq!(course.trace_line(Movement::Hold), "Hold\t2\t5\t0");
+    }
+    #[test]
+    fn check_course_trace_line() {
+        let course = Course::default()
+            .steer(Movement::Forward(5))
+            .steer(Movement::Down(5))
+            .steer(Movement::Forward(8));
+
+        assert_eq!(
+            course.trace_line(Movement::Forward(8)),
+            "Forward(8)\t5\t13\t0\t40\t13\t5"
+        );
+    }
+    #[test]
+    fn check_waypoint_trace() {
+        let course = Course::new(vec![Box::new(WaypointDive)])
+            .steer(Movement::Forward(5))
+            .steer(Movement::Down(5));
+        assert_eq!(
+            course.trace_header(),
+            "Movement\tWaypoint depth\tWaypoint horizontal\t\
+             Waypoint waypoint depth\tWaypoint waypoint horizontal"
+        );
+        assert_eq!(
+            course.trace_line(Movement::Down(5)),
+            "Down(5)\t5\t50\t6\t10"
+        );
+    }
+    #[test]
+    fn check_plot() {
+        let origin = Position::default();
+        let trajectories = vec![
+            (
+                "Simple",
+                vec![
+                    origin,
+                    Position {
+                        horizontal: 4,
+                        ..origin
+                    },
+                    Position {
+                        horizontal: 4,
+                        depth: 2,
+                        ..origin
+                    },
+                ],
+            ),
+            (
+                "Aimed",
+                vec![
+                    origin,
+                    Position {
+                        horizontal: 4,
+                        depth: 4,
+                        ..origin
+                    },
+                ],
+            ),
+        ];
+
+        assert_eq!(
+            plot(&trajectories, 5, 3),
+            "Horizontal 0..4 across, depth 0..4 down\n\
+             |AASSS|\n\
+             |  AAS|\n\
+             |    A|\n"
+        );
+    }
+}
@@ -0,0 +1,636 @@
+use std::io::BufRead;
+
+#[derive(Default)]
+struct Counter {
+    count: i16,
+    bit_counts: [i16; 16],
+}
+
+fn most_common_bit_scan(counter: &mut Counter, value: &i16) -> Option<i16> {
+    let mut bit_counts: [i16; 16] = [0; 16];
+    let mut result = 0_i16;
+
+    let count = counter.count + 1;
+    // Add 1 to round up when dividing by 2
+    let half_way_count = (count + 1) / 2;
+
+    for (n, bit_count) in bit_counts.iter_mut().enumerate() {
+        *bit_count = counter.bit_counts[n] + has_bit_at(value, n) as i16;
+
+        if *bit_count >= half_way_count {
+            result += 1 << n;
+        }
+    }
+
+    *counter = Counter { count, bit_counts };
+    Some(result)
+}
+
+/// Which value counts as the most common when a bit is set in exactly half of
+/// the readings.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TieBreak {
+    #[default]
+    PreferOne,
+    PreferZero,
+    Error,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RatingError {
+    /// A tie was found at this bit position while using [`TieBreak::Error`].
+    Tie { position: usize },
+    /// Filtering did not narrow the readings down to exactly one.
+    NoSingleReading,
+}
+
+impl std::fmt::Display for RatingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RatingError::Tie { position } => write!(f, "Tie at bit position {}", position),
+            RatingError::NoSingleReading => write!(f, "No single reading remained"),
+        }
+    }
+}
+
+fn most_common_bit_at(
+    readings: &[i16],
+    bit_index: usize,
+    tie: TieBreak,
+) -> Result<bool, RatingError> {
+    let ones = readings.iter().filter(|r| has_bit_at(r, bit_index)).count();
+    most_common_of(ones, readings.len() - ones, bit_index, tie)
+}
+
+fn most_common_of(
+    ones: usize,
+    zeros: usize,
+    bit_index: usize,
+    tie: TieBreak,
+) -> Result<bool, RatingError> {
+    match (ones.cmp(&zeros), tie) {
+        (std::cmp::Ordering::Greater, _) => Ok(true),
+        (std::cmp::Ordering::Less, _) => Ok(false),
+        (std::cmp::Ordering::Equal, TieBreak::PreferOne) => Ok(true),
+        (std::cmp::Ordering::Equal, TieBreak::PreferZero) => Ok(false),
+        (std::cmp::Ordering::Equal, TieBreak::Error) => Err(RatingError::Tie {
+            position: bit_index,
+        }),
+    }
+}
+
+fn get_oxygen_generator_rating(
+    mut current_readings: Vec<i16>,
+    bit_index: usize,
+    tie: TieBreak,
+) -> Result<Vec<i16>, RatingError> {
+    if current_readings.len() > 1 {
+        let has_bit = most_common_bit_at(&current_readings, bit_index, tie)?;
+        current_readings.retain(|r| has_bit_at(r, bit_index) == has_bit);
+    }
+    Ok(current_readings)
+}
+
+fn get_scrubber_rating(
+    mut current_readings: Vec<i16>,
+    bit_index: usize,
+    tie: TieBreak,
+) -> Result<Vec<i16>, RatingError> {
+    if current_readings.len() > 1 {
+        let has_bit = most_common_bit_at(&current_readings, bit_index, tie)?;
+        current_readings.retain(|r| has_bit_at(r, bit_index) != has_bit);
+    }
+    Ok(current_readings)
+}
+
+fn get_most_common_bits(readings: &[i16]) -> Option<i16> {
+    let m = readings
+        .iter()
+        .scan(Counter::default(), most_common_bit_scan)
+        .collect::<Vec<i16>>();
+
+    m.last().map(|a| a.to_owned())
+}
+
+fn has_bit_at(value: &i16, position: usize) -> bool {
+    ((value >> position) & 1) > 0
+}
+
+/// How often a single bit position is set across all readings.
+#[derive(Debug, PartialEq)]
+pub struct BitCount {
+    pub position: usize,
+    pub ones: usize,
+    pub zeros: usize,
+}
+
+impl BitCount {
+    /// The most common value, with ties broken by `tie`.
+    pub fn most_common(&self, tie: TieBreak) -> Result<u8, RatingError> {
+        most_common_of(self.ones, self.zeros, self.position, tie).map(u8::from)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// A character other than `0` or `1` was found at `index`.
+    InvalidCharacter {
+        line_number: usize,
+        line: String,
+        index: usize,
+    },
+    /// The line has more bits than a reading can hold.
+    TooWide {
+        line_number: usize,
+        line: String,
+    },
+    Empty {
+        line_number: usize,
+    },
+    Io(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidCharacter {
+                line_number,
+                line,
+                index,
+            } => write!(
+                f,
+                "Line {} ({:?}) has a non-binary character at index {}",
+                line_number, line, index
+            ),
+            ParseError::TooWide { line_number, line } => {
+                write!(f, "Line {} ({:?}) has too many bits", line_number, line)
+            }
+            ParseError::Empty { line_number } => write!(f, "Line {} is empty", line_number),
+            ParseError::Io(err) => write!(f, "Unable to read input: {}", err),
+        }
+    }
+}
+
+const MAX_WIDTH: usize = 15;
+
+fn parse_reading(line: &str, line_number: usize) -> Result<i16, ParseError> {
+    if let Some(index) = line.chars().position(|c| c != '0' && c != '1') {
+        return Err(ParseError::InvalidCharacter {
+            line_number,
+            line: line.to_owned(),
+            index,
+        });
+    }
+    match line.len() {
+        0 => Err(ParseError::Empty { line_number }),
+        len if len > MAX_WIDTH => Err(ParseError::TooWide {
+            line_number,
+            line: line.to_owned(),
+        }),
+        _ => Ok(line
+            .chars()
+            .fold(0, |reading, c| (reading << 1) | i16::from(c == '1'))),
+    }
+}
+
+/// Reads every line, numbering them from 1.
+fn read_lines(read: impl BufRead) -> impl Iterator<Item = Result<(usize, String), ParseError>> {
+    read.lines().zip(1..).map(|(line, line_number)| {
+        line.map(|l| (line_number, l))
+            .map_err(|err| ParseError::Io(err.to_string()))
+    })
+}
+
+/// Running per-bit counts of readings, enough to find the gamma and epsilon
+/// rates without holding on to every reading.
+#[derive(Debug, Default, PartialEq)]
+pub struct BitCounter {
+    count: usize,
+    ones: Vec<usize>,
+}
+
+impl BitCounter {
+    /// Reads one binary number per line, counting bits as each line arrives.
+    pub fn from_reader(read: impl BufRead) -> Result<Self, ParseError> {
+        let mut counter = BitCounter::default();
+        for line in read_lines(read) {
+            let (line_number, line) = line?;
+            counter.push(parse_reading(&line, line_number)?, line.len());
+        }
+        Ok(counter)
+    }
+
+    pub fn push(&mut self, reading: i16, width: usize) {
+        if self.ones.len() < width {
+            self.ones.resize(width, 0);
+        }
+        for (position, ones) in self.ones.iter_mut().enumerate() {
+            *ones += usize::from(has_bit_at(&reading, position));
+        }
+        self.count += 1;
+    }
+
+    pub fn gamma(&self) -> Option<i16> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(
+            self.ones
+                .iter()
+                .enumerate()
+                .filter(|(_, &ones)| ones * 2 >= self.count)
+                .fold(0, |gamma, (position, _)| gamma | (1 << position)),
+        )
+    }
+
+    pub fn epsilon(&self) -> Option<i16> {
+        let mask = ((1_i32 << self.ones.len()) - 1) as i16;
+        self.gamma().map(|gamma| gamma ^ mask)
+    }
+
+    pub fn power_consumption(&self) -> Option<i32> {
+        Some(i32::from(self.gamma()?) * i32::from(self.epsilon()?))
+    }
+}
+
+/// The readings from a diagnostic report, each `width` bits wide.
+#[derive(Debug, PartialEq)]
+pub struct DiagnosticReport {
+    readings: Vec<i16>,
+    width: usize,
+}
+
+impl DiagnosticReport {
+    pub fn new(readings: Vec<i16>, width: usize) -> Self {
+        DiagnosticReport { readings, width }
+    }
+
+    /// Reads one binary number per line, taking the width from the longest
+    /// line.
+    pub fn from_reader(read: impl BufRead) -> Result<Self, ParseError> {
+        let mut readings = Vec::new();
+        let mut width = 0;
+        for line in read_lines(read) {
+            let (line_number, line) = line?;
+            readings.push(parse_reading(&line, line_number)?);
+            width = width.max(line.len());
+        }
+        Ok(DiagnosticReport { readings, width })
+    }
+
+    fn mask(&self) -> i16 {
+        ((1_i32 << self.width) - 1) as i16
+    }
+
+    /// Counts of each bit, from the most significant position down.
+    pub fn histogram(&self) -> Vec<BitCount> {
+        (0..self.width)
+            .rev()
+            .map(|position| {
+                let ones = self
+                    .readings
+                    .iter()
+                    .filter(|r| has_bit_at(r, position))
+                    .count();
+                BitCount {
+                    position,
+                    ones,
+                    zeros: self.readings.len() - ones,
+                }
+            })
+            .collect()
+    }
+
+    pub fn gamma(&self) -> Option<i16> {
+        get_most_common_bits(&self.readings)
+    }
+
+    pub fn epsilon(&self) -> Option<i16> {
+        self.gamma().map(|gamma| gamma ^ self.mask())
+    }
+
+    pub fn oxygen_rating(&self) -> Option<i16> {
+        self.oxygen_rating_with(TieBreak::default()).ok()
+    }
+
+    pub fn oxygen_rating_with(&self, tie: TieBreak) -> Result<i16, RatingError> {
+        let remaining = (0..self.width)
+            .rev()
+            .try_fold(self.readings.clone(), |readings, bit_index| {
+                get_oxygen_generator_rating(readings, bit_index, tie)
+            })?;
+        match remaining[..] {
+            [reading] => Ok(reading),
+            _ => Err(RatingError::NoSingleReading),
+        }
+    }
+
+    pub fn co2_rating(&self) -> Option<i16> {
+        self.co2_rating_with(TieBreak::default()).ok()
+    }
+
+    pub fn co2_rating_with(&self, tie: TieBreak) -> Result<i16, RatingError> {
+        let remaining = (0..self.width)
+            .rev()
+            .try_fold(self.readings.clone(), |readings, bit_index| {
+                get_scrubber_rating(readings, bit_index, tie)
+            })?;
+        match remaining[..] {
+            [reading] => Ok(reading),
+            _ => Err(RatingError::NoSingleReading),
+        }
+    }
+
+    pub fn power_consumption(&self) -> Option<i32> {
+        let gamma = self.gamma()?;
+        let epsilon = self.epsilon()?;
+        Some(i32::from(gamma) * i32::from(epsilon))
+    }
+
+    pub fn life_support_rating(&self) -> Option<i32> {
+        self.life_support_rating_with(TieBreak::default()).ok()
+    }
+
+    pub fn life_support_rating_with(&self, tie: TieBreak) -> Result<i32, RatingError> {
+        let oxygen = self.oxygen_rating_with(tie)?;
+        let co2 = self.co2_rating_with(tie)?;
+        Ok(i32::from(oxygen) * i32::from(co2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_simple_case() -> Vec<i16> {
+        vec![
+            0b00100, // 01
+            0b11110, // 02
+            0b10110, // 03
+            0b10111, // 04
+            0b10101, // 05
+            0b01111, // 06
+            0b00111, // 07
+            0b11100, // 08
+            0b10000, // 09
+            0b11001, // 10
+            0b00010, // 11
+            0b01010, // 12
+        ]
+    }
+
+    #[test]
+    fn check_most_common_bit_scan() {
+        let simple_case = get_simple_case();
+        let results = simple_case
+            .iter()
+            .scan(Counter::default(), most_common_bit_scan);
+
+        let expectation: Vec<i16> = vec![
+            0b00100, // 01 | 0b00100 | 0 0 1 0 0
+            0b11110, // 02 | 0b11110 | 1 1 2 1 0
+            0b10110, // 03 | 0b10110 | 2 1 3 2 0
+            0b10110, // 04 | 0b10111 | 3 1 4 3 1
+            0b10110, // 05 | 0b10101 | 4 1 5 3 2
+            0b10111, // 06 | 0b01111 | 4 2 6 4 3
+            0b10111, // 07 | 0b00111 | 4 2 7 5 4
+            0b10111, // 08 | 0b11100 | 5 3 8 5 4
+            0b10110, // 09 | 0b10000 | 6 3 8 5 4
+            0b10111, // 10 | 0b11001 | 7 4 8 5 5
+            0b10110, // 11 | 0b00010 | 7 4 8 6 5
+            0b10110, // 12 | 0b01010 | 7 5 8 7 5
+        ];
+        for ((actual, expected), index) in results.zip(&expectation).zip(1..) {
+            assert_eq!(
+                actual, *expected,
+                "for step:{} actual:{:#07b} expected:{:#07b}",
+                index, actual, expected
+            );
+        }
+    }
+
+    #[test]
+    fn check_get_oxygen_generator_rating() {
+        let simple_case = get_simple_case();
+        let results = (0..5)
+            .rev()
+            .try_fold(simple_case.clone(), |r, i| {
+                get_oxygen_generator_rating(r, i, TieBreak::PreferOne)
+            })
+            .unwrap();
+
+        assert!(matches!(results[..], [reading] if reading == 23));
+    }
+
+    #[test]
+    fn check_get_oxygen_generator_rating_by_step() {
+        let mut results =
+            get_oxygen_generator_rating(get_simple_case(), 4, TieBreak::PreferOne).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                0b11110, // 02
+                0b10110, // 03
+                0b10111, // 04
+                0b10101, // 05
+                0b11100, // 08
+                0b10000, // 09
+                0b11001, // 10
+            ]
+        );
+        results = get_oxygen_generator_rating(results, 3, TieBreak::PreferOne).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                0b10110, // 03
+                0b10111, // 04
+                0b10101, // 05
+                0b10000, // 09
+            ]
+        );
+        results = get_oxygen_generator_rating(results, 2, TieBreak::PreferOne).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                0b10110, // 03
+                0b10111, // 04
+                0b10101, // 05
+            ]
+        );
+        results = get_oxygen_generator_rating(results, 1, TieBreak::PreferOne).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                0b10110, // 03
+                0b10111, // 04
+            ]
+        );
+        results = get_oxygen_generator_rating(results, 0, TieBreak::PreferOne).unwrap();
+        assert_eq!(results, vec![0b10111]);
+    }
+
+    #[test]
+    fn check_get_scrubber_rating() {
+        let simple_case = get_simple_case();
+        let results = (0..5)
+            .rev()
+            .try_fold(simple_case.clone(), |r, i| {
+                get_scrubber_rating(r, i, TieBreak::PreferOne)
+            })
+            .unwrap();
+
+        assert!(matches!(results[..], [reading] if reading == 10));
+    }
+
+    #[test]
+    fn check_get_scrubber_rating_by_step() {
+        let mut results = get_scrubber_rating(get_simple_case(), 4, TieBreak::PreferOne).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                0b00100, // 01
+                0b01111, // 06
+                0b00111, // 07
+                0b00010, // 11
+                0b01010, // 12
+            ]
+        );
+        results = get_scrubber_rating(results, 3, TieBreak::PreferOne).unwrap();
+        assert_eq!(
+            results,
+            vec![
+                0b01111, // 06
+                0b01010, // 12
+            ]
+        );
+        results = get_scrubber_rating(results, 2, TieBreak::PreferOne).unwrap();
+        assert_eq!(results, vec![0b01010]);
+        results = get_scrubber_rating(results, 1, TieBreak::PreferOne).unwrap();
+        assert_eq!(results, vec![0b01010]);
+        results = get_scrubber_rating(results, 0, TieBreak::PreferOne).unwrap();
+        assert_eq!(results, vec![0b01010]);
+    }
+
+    #[test]
+    fn check_diagnostic_report() {
+        let report = DiagnosticReport::new(get_simple_case(), 5);
+
+        assert_eq!(report.gamma(), Some(22));
+        assert_eq!(report.epsilon(), Some(9));
+        assert_eq!(report.power_consumption(), Some(198));
+        assert_eq!(report.oxygen_rating(), Some(23));
+        assert_eq!(report.co2_rating(), Some(10));
+        assert_eq!(report.life_support_rating(), Some(230));
+    }
+
+    #[test]
+    fn check_tie_break() {
+        let report = DiagnosticReport::new(get_simple_case(), 5);
+
+        assert_eq!(report.oxygen_rating_with(TieBreak::PreferOne), Ok(23));
+        assert_eq!(report.co2_rating_with(TieBreak::PreferOne), Ok(10));
+        assert_eq!(report.oxygen_rating_with(TieBreak::PreferZero), Ok(22));
+        assert_eq!(report.co2_rating_with(TieBreak::PreferZero), Ok(15));
+        assert_eq!(
+            report.oxygen_rating_with(TieBreak::Error),
+            Err(RatingError::Tie { position: 0 })
+        );
+        assert_eq!(
+            report.co2_rating_with(TieBreak::Error),
+            Err(RatingError::Tie { position: 2 })
+        );
+    }
+
+    #[test]
+    fn check_bit_counter() {
+        let mut counter = BitCounter::default();
+        for reading in get_simple_case() {
+            counter.push(reading, 5);
+        }
+
+        assert_eq!(counter.gamma(), Some(22));
+        assert_eq!(counter.epsilon(), Some(9));
+        assert_eq!(counter.power_consumption(), Some(198));
+        assert_eq!(BitCounter::default().gamma(), None);
+    }
+
+    #[test]
+    fn check_bit_counter_from_reader() {
+        let counter =
+            BitCounter::from_reader(std::io::Cursor::new(b"00100\n11110\n10110")).unwrap();
+        assert_eq!(counter.gamma(), Some(0b10110));
+        assert_eq!(counter.epsilon(), Some(0b01001));
+    }
+
+    #[test]
+    fn check_histogram() {
+        let report = DiagnosticReport::new(get_simple_case(), 5);
+        let histogram = report.histogram();
+
+        assert_eq!(
+            histogram
+                .iter()
+                .map(|b| (
+                    b.position,
+                    b.ones,
+                    b.zeros,
+                    b.most_common(TieBreak::PreferOne).unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (4, 7, 5, 1),
+                (3, 5, 7, 0),
+                (2, 8, 4, 1),
+                (1, 7, 5, 1),
+                (0, 5, 7, 0),
+            ]
+        );
+
+        let tied = DiagnosticReport::new(vec![0b10, 0b01], 2).histogram();
+        assert_eq!(tied[0].most_common(TieBreak::PreferOne), Ok(1));
+        assert_eq!(tied[0].most_common(TieBreak::PreferZero), Ok(0));
+        assert_eq!(
+            tied[0].most_common(TieBreak::Error),
+            Err(RatingError::Tie { position: 1 })
+        );
+    }
+
+    #[test]
+    fn check_diagnostic_report_from_reader() {
+        let report = DiagnosticReport::from_reader(std::io::Cursor::new(b"00100\n11110\n"));
+        assert_eq!(report, Ok(DiagnosticReport::new(vec![0b00100, 0b11110], 5)));
+    }
+
+    #[test]
+    fn check_parse_errors() {
+        assert_eq!(
+            DiagnosticReport::from_reader(std::io::Cursor::new(b"00100\n11210\n")),
+            Err(ParseError::InvalidCharacter {
+                line_number: 2,
+                line: "11210".to_owned(),
+                index: 2
+            })
+        );
+        assert_eq!(
+            BitCounter::from_reader(std::io::Cursor::new(b"00100\n\n11110")),
+            Err(ParseError::Empty { line_number: 2 })
+        );
+        assert_eq!(
+            parse_reading("1111111111111111", 3),
+            Err(ParseError::TooWide {
+                line_number: 3,
+                line: "1111111111111111".to_owned()
+            })
+        );
+        assert_eq!(parse_reading("111111111111111", 3), Ok(i16::MAX));
+    }
+
+    #[test]
+    fn check_empty_diagnostic_report() {
+        let report = DiagnosticReport::new(vec![], 5);
+
+        assert_eq!(report.gamma(), None);
+        assert_eq!(report.power_consumption(), None);
+        assert_eq!(report.life_support_rating(), None);
+    }
+}
@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader};
 
 struct BingoBoard {
     id: usize,
+    cells: [i16; 25],
+    marked: [bool; 25],
     mapping: HashMap<i16, (usize, usize)>,
     score: i16,
     columns: [i8; 5],
@@ -15,11 +17,13 @@ impl BingoBoard {
         let mut score = 0i16;
         let mut mapping = HashMap::new();
         for (index, cell) in cells.iter().enumerate() {
-            score = score + cell;
+            score += cell;
             mapping.insert(cell.to_owned(), (index / 5, index % 5));
         }
         BingoBoard {
             id,
+            cells,
+            marked: [false; 25],
             columns: [5; 5],
             mapping,
             rows: [5; 5],
@@ -33,6 +37,7 @@ impl BingoBoard {
             None
         } else if let Some((x, y)) = self.mapping.get(&value) {
             self.score -= value;
+            self.marked[x * 5 + y] = true;
             self.columns[*x] -= 1;
             self.rows[*y] -= 1;
             self.won = (self.columns[*x] == 0) || (self.rows[*y] == 0);
@@ -44,150 +49,401 @@ impl BingoBoard {
     }
 }
 
-enum GameDefinition {
-    Initial,
-    WithNumbersDrawn(Vec<i16>),
-    WithGameboardCells(Vec<i16>, Vec<i16>),
+/// Shows the grid with drawn numbers in brackets.
+impl std::fmt::Display for BingoBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (row, (cells, marked)) in self.cells.chunks(5).zip(self.marked.chunks(5)).enumerate() {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            let line = cells
+                .iter()
+                .zip(marked)
+                .map(|(cell, &marked)| match marked {
+                    true => format!("[{:>2}]", cell),
+                    false => format!(" {:>2} ", cell),
+                })
+                .collect::<String>();
+            write!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
 }
 
-fn build_game_definition(game_definition: GameDefinition, line: String) -> GameDefinition {
-    match game_definition {
-        GameDefinition::Initial => {
-            let numbers_drawn = line
-                .split(",")
-                .filter_map(|z| z.parse::<i16>().ok())
-                .collect();
-            GameDefinition::WithNumbersDrawn(numbers_drawn)
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    MissingNumbersDrawn,
+    InvalidNumber { line_number: usize, value: String },
+    WrongCellCount { board: usize, count: usize },
+    DuplicateNumber { board: usize, value: i16 },
+    NumberOnNoBoard { value: i16 },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingNumbersDrawn => write!(f, "Missing the numbers drawn"),
+            ParseError::InvalidNumber { line_number, value } => {
+                write!(f, "Line {} has an invalid number {:?}", line_number, value)
+            }
+            ParseError::WrongCellCount { board, count } => {
+                write!(f, "Board {} has {} cells instead of 25", board, count)
+            }
+            ParseError::DuplicateNumber { board, value } => {
+                write!(f, "Board {} has {} more than once", board, value)
+            }
+            ParseError::NumberOnNoBoard { value } => {
+                write!(f, "Drawn number {} is not on any board", value)
+            }
         }
-        GameDefinition::WithNumbersDrawn(n) => {
-            let cells = line
-                .split(" ")
-                .filter_map(|i| i.parse::<i16>().ok())
-                .collect();
-            GameDefinition::WithGameboardCells(n, cells)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct GameDefinition {
+    numbers_drawn: Vec<i16>,
+    boards: Vec<[i16; 25]>,
+}
+
+fn parse_numbers(
+    line_number: usize,
+    line: &str,
+    separator: fn(char) -> bool,
+) -> Result<Vec<i16>, ParseError> {
+    line.split(separator)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value.parse::<i16>().map_err(|_| ParseError::InvalidNumber {
+                line_number,
+                value: value.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the drawn numbers followed by blank line separated boards.
+fn build_game_definition(lines: &[String]) -> Result<GameDefinition, ParseError> {
+    let mut blocks = lines
+        .iter()
+        .zip(1..)
+        .collect::<Vec<_>>()
+        .split(|(line, _)| line.trim().is_empty())
+        .filter(|block| !block.is_empty())
+        .map(|block| block.to_vec())
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    let numbers_drawn = match blocks.next().as_deref() {
+        Some([(line, line_number)]) => parse_numbers(*line_number, line, |c| c == ',')?,
+        _ => return Err(ParseError::MissingNumbersDrawn),
+    };
+
+    let mut boards = Vec::new();
+    for (board, block) in blocks.enumerate() {
+        let mut cells = Vec::new();
+        for (line, line_number) in block {
+            cells.extend(parse_numbers(line_number, line, char::is_whitespace)?);
         }
-        GameDefinition::WithGameboardCells(n, mut c) => {
-            c.extend(line.split(" ").filter_map(|i| i.parse::<i16>().ok()));
-            GameDefinition::WithGameboardCells(n, c)
+        let cells: [i16; 25] =
+            cells
+                .as_slice()
+                .try_into()
+                .map_err(|_| ParseError::WrongCellCount {
+                    board,
+                    count: cells.len(),
+                })?;
+        let mut seen = HashSet::new();
+        if let Some(value) = cells.iter().find(|cell| !seen.insert(**cell)) {
+            return Err(ParseError::DuplicateNumber {
+                board,
+                value: *value,
+            });
         }
+        boards.push(cells);
     }
+
+    let on_boards = boards.iter().flatten().collect::<HashSet<_>>();
+    if let Some(value) = numbers_drawn.iter().find(|n| !on_boards.contains(n)) {
+        return Err(ParseError::NumberOnNoBoard { value: *value });
+    }
+
+    Ok(GameDefinition {
+        numbers_drawn,
+        boards,
+    })
 }
 
-fn run_board(board: BingoBoard, numbers_drawn: &Vec<i16>) -> (usize, usize, i32) {
-    let id = board.id;
-    let scores: Vec<i32> = numbers_drawn
-        .iter()
-        .scan(board, |b, number_drawn| b.number_drawn(*number_drawn))
-        .collect();
+/// A winning board's `(id, moves, score)`.
+type BoardResult = (usize, usize, i32);
 
-    scores
-        .last()
-        .map(|s| (id, scores.len(), s.to_owned()))
-        .unwrap()
+/// Draws numbers one at a time across every board, yielding each board's
+/// `(id, moves, score)` as it wins. Drawing stops once every board has won.
+struct BingoGame {
+    boards: Vec<BingoBoard>,
+    numbers_drawn: std::vec::IntoIter<i16>,
+    moves: usize,
+    winners: std::collections::VecDeque<BoardResult>,
 }
 
-fn main() {
-    let game_definition = BufReader::new(std::io::stdin())
-        .lines()
-        .filter_map(Result::ok)
-        .fold(GameDefinition::Initial, build_game_definition);
-
-    match game_definition {
-        GameDefinition::WithGameboardCells(numbers_drawn, cells) => {
-            let boards: Vec<BingoBoard> = cells
-                .chunks_exact(25)
-                .enumerate()
-                .map(|(i, x)| {
-                    let mut cells = [0; 25];
-                    for index in 0..25 {
-                        cells[index] = x[index];
-                    }
-                    BingoBoard::new(i, cells)
-                })
-                .collect();
+impl BingoGame {
+    fn new(boards: Vec<BingoBoard>, numbers_drawn: Vec<i16>) -> BingoGame {
+        BingoGame {
+            boards,
+            numbers_drawn: numbers_drawn.into_iter(),
+            moves: 0,
+            winners: Default::default(),
+        }
+    }
 
-            let mut results: Vec<(usize, usize, i32)> = boards
-                .into_iter()
-                .map(|b| run_board(b, &numbers_drawn))
-                .collect();
+    fn into_boards(self) -> Vec<BingoBoard> {
+        self.boards
+    }
+}
 
-            results.sort_by(|(_, a, _), (_, b, _)| a.partial_cmp(b).unwrap());
+impl Iterator for BingoGame {
+    type Item = BoardResult;
 
-            if let Some((id, moves, score)) = results.first() {
-                println!(
-                    "Game no.{} completed the first in {} moves, with score {}",
-                    id, moves, score
-                );
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.winners.is_empty() {
+            if self.boards.iter().all(|b| b.won) {
+                return None;
             }
-            if let Some((id, moves, score)) = results.last() {
-                println!(
-                    "Game no.{} completed the last in {} moves, with score {}",
-                    id, moves, score
-                );
+            let number_drawn = self.numbers_drawn.next()?;
+            self.moves += 1;
+            for board in self.boards.iter_mut() {
+                if let Some(score) = board.number_drawn(number_drawn) {
+                    if board.won {
+                        self.winners.push_back((board.id, self.moves, score));
+                    }
+                }
             }
         }
-        _ => {
-            println!("Could not parse input");
+        self.winners.pop_front()
+    }
+}
+
+/// The outcome of playing every board.
+struct Played {
+    /// Sorted by moves then board id.
+    winners: Vec<BoardResult>,
+    /// Ids of the boards that had not won when the numbers ran out.
+    never_won: Vec<usize>,
+    /// The boards as they finished, sorted by id.
+    boards: Vec<BingoBoard>,
+}
+
+/// Plays the boards split across `threads` threads.
+fn play(boards: Vec<BingoBoard>, numbers_drawn: Vec<i16>, threads: usize) -> Played {
+    let chunk_size = boards.len().div_ceil(threads.max(1)).max(1);
+    let mut chunks = Vec::new();
+    let mut boards = boards.into_iter().peekable();
+    while boards.peek().is_some() {
+        chunks.push(boards.by_ref().take(chunk_size).collect::<Vec<_>>());
+    }
+
+    let played = std::thread::scope(|scope| {
+        let handles = chunks
+            .into_iter()
+            .map(|chunk| {
+                let numbers_drawn = numbers_drawn.clone();
+                scope.spawn(move || {
+                    let mut game = BingoGame::new(chunk, numbers_drawn);
+                    let results = game.by_ref().collect::<Vec<_>>();
+                    (results, game.into_boards())
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("board thread panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    let mut winners = Vec::new();
+    let mut boards = Vec::new();
+    for (results, finished) in played {
+        winners.extend(results);
+        boards.extend(finished);
+    }
+    winners.sort_by_key(|(id, moves, _)| (*moves, *id));
+    boards.sort_by_key(|b| b.id);
+    let never_won = boards.iter().filter(|b| !b.won).map(|b| b.id).collect();
+    Played {
+        winners,
+        never_won,
+        boards,
+    }
+}
+
+/// Writes the ranking from the winners and the boards that never won.
+type RankingFormat = fn(&[BoardResult], &[usize]) -> String;
+
+/// Every board with its rank, the winners in winning order and then the
+/// boards that never won, which have no moves or score.
+fn ranked<'a>(
+    results: &'a [BoardResult],
+    never_won: &'a [usize],
+) -> impl Iterator<Item = (usize, usize, Option<(usize, i32)>)> + 'a {
+    results
+        .iter()
+        .map(|(id, moves, score)| (*id, Some((*moves, *score))))
+        .chain(never_won.iter().map(|id| (*id, None)))
+        .zip(1..)
+        .map(|((id, result), rank)| (rank, id, result))
+}
+
+/// One `rank,board,moves,score` row per board, with the boards that never
+/// won last and their moves and score left empty.
+fn ranking_csv(results: &[BoardResult], never_won: &[usize]) -> String {
+    ranked(results, never_won)
+        .map(|(rank, id, result)| match result {
+            Some((moves, score)) => format!("{},{},{},{}\n", rank, id, moves, score),
+            None => format!("{},{},,\n", rank, id),
+        })
+        .fold("rank,board,moves,score\n".to_owned(), |csv, row| csv + &row)
+}
+
+/// An array of `{rank, board, moves, score}` objects, with the boards that
+/// never won last and their moves and score `null`.
+fn ranking_json(results: &[BoardResult], never_won: &[usize]) -> String {
+    let rows = ranked(results, never_won)
+        .map(|(rank, id, result)| {
+            let (moves, score) = match result {
+                Some((moves, score)) => (moves.to_string(), score.to_string()),
+                None => ("null".to_owned(), "null".to_owned()),
+            };
+            format!(
+                "  {{\"rank\": {}, \"board\": {}, \"moves\": {}, \"score\": {}}}",
+                rank, id, moves, score
+            )
+        })
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        "[]\n".to_owned()
+    } else {
+        format!("[\n{}\n]\n", rows.join(",\n"))
+    }
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let ranking: Option<RankingFormat> =
+        match args.iter().find_map(|a| a.strip_prefix("--ranking=")) {
+            None => None,
+            Some("csv") => Some(ranking_csv),
+            Some("json") => Some(ranking_json),
+            Some(other) => return Err(format!("Unknown ranking format: {}", other)),
+        };
+    let threads = match args.iter().find_map(|a| a.strip_prefix("--threads=")) {
+        Some(t) => t.parse::<usize>().map_err(|err| err.to_string())?,
+        None => 1,
+    };
+
+    let lines = BufReader::new(std::io::stdin())
+        .lines()
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|err| err.to_string())?;
+    let game_definition = build_game_definition(&lines).map_err(|err| err.to_string())?;
+
+    let boards: Vec<BingoBoard> = game_definition
+        .boards
+        .into_iter()
+        .enumerate()
+        .map(|(i, cells)| BingoBoard::new(i, cells))
+        .collect();
+
+    let show_winner = args.iter().any(|a| a == "--show-winner");
+    let played = play(boards, game_definition.numbers_drawn, threads);
+
+    if let Some(format) = ranking {
+        print!("{}", format(&played.winners, &played.never_won));
+        return Ok(());
+    }
+
+    let results = &played.winners;
+    for (label, result) in [("first", results.first()), ("last", results.last())] {
+        if let Some((id, moves, score)) = result {
+            println!(
+                "Game no.{} completed the {} in {} moves, with score {}",
+                id, label, moves, score
+            );
+            if show_winner {
+                println!("{}", played.boards[*id]);
+            }
         }
     }
+    for id in &played.never_won {
+        println!("Game no.{} never completed", id);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn check_build_game_definition_for_initial_state() {
-        let result = build_game_definition(GameDefinition::Initial, "7,4,9,5,11".to_owned());
-        let expected = vec![7, 4, 9, 5, 11];
-
-        match result {
-            GameDefinition::WithNumbersDrawn(numbers_drawn) => {
-                assert_eq!(numbers_drawn, expected);
-            }
-            _ => {
-                assert!(false, "Invalid Game Definition state");
-            }
-        }
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(str::to_owned).collect()
     }
 
     #[test]
-    fn check_build_game_definition_for_with_numbers_drawn_state() {
-        let numbers_drawn = vec![7, 4, 9, 5, 11];
-        let result = build_game_definition(
-            GameDefinition::WithNumbersDrawn(numbers_drawn.clone()),
-            "2 14 19 25 11".to_owned(),
-        );
+    fn check_build_game_definition() {
+        let input = "7,4,9,5,11\n\n\
+             22 13 17 11  0\n 8  2 23  4 24\n21  9 14 16  7\n 6 10  3 18  5\n 1 12 20 15 19\n\n\
+              3 15  0  2 22\n 9 18 13 17  5\n19  8  7 25 23\n20 11 10 24  4\n14 21 16 12  6\n";
 
-        match result {
-            GameDefinition::WithGameboardCells(n, c) => {
-                assert_eq!(n, numbers_drawn);
-                assert_eq!(c, vec![2, 14, 19, 25, 11]);
-            }
-            _ => {
-                assert!(false, "Invalid Game Definition state");
-            }
-        }
+        assert_eq!(
+            build_game_definition(&lines(input)),
+            Ok(GameDefinition {
+                numbers_drawn: vec![7, 4, 9, 5, 11],
+                boards: vec![
+                    [
+                        22, 13, 17, 11, 0, 8, 2, 23, 4, 24, 21, 9, 14, 16, 7, 6, 10, 3, 18, 5, 1,
+                        12, 20, 15, 19
+                    ],
+                    [
+                        3, 15, 0, 2, 22, 9, 18, 13, 17, 5, 19, 8, 7, 25, 23, 20, 11, 10, 24, 4, 14,
+                        21, 16, 12, 6
+                    ],
+                ],
+            })
+        );
     }
 
     #[test]
-    fn check_build_game_definition_for_with_gameboard_cells_state() {
-        let numbers_drawn = vec![7, 4, 9, 5, 11];
-        let cells = vec![2, 14, 19, 25, 11];
-        let result = build_game_definition(
-            GameDefinition::WithGameboardCells(numbers_drawn.clone(), cells),
-            "18 8 23 26 20".to_owned(),
-        );
+    fn check_build_game_definition_errors() {
+        let board =
+            "22 13 17 11  0\n 8  2 23  4 24\n21  9 14 16  7\n 6 10  3 18  5\n 1 12 20 15 19";
 
-        match result {
-            GameDefinition::WithGameboardCells(n, c) => {
-                assert_eq!(n, numbers_drawn);
-                assert_eq!(c, vec![2, 14, 19, 25, 11, 18, 8, 23, 26, 20]);
-            }
-            _ => {
-                assert!(false, "Invalid Game Definition state");
-            }
-        }
+        assert_eq!(
+            build_game_definition(&lines("")),
+            Err(ParseError::MissingNumbersDrawn)
+        );
+        assert_eq!(
+            build_game_definition(&lines(&format!("7,x,9\n\n{}", board))),
+            Err(ParseError::InvalidNumber {
+                line_number: 1,
+                value: "x".to_owned()
+            })
+        );
+        assert_eq!(
+            build_game_definition(&lines(&format!("7,4\n\n{}\n\n{} 99", board, board))),
+            Err(ParseError::WrongCellCount {
+                board: 1,
+                count: 26
+            })
+        );
+        assert_eq!(
+            build_game_definition(&lines(&format!("7,4\n\n{}", board.replace("22", "19")))),
+            Err(ParseError::DuplicateNumber {
+                board: 0,
+                value: 19
+            })
+        );
+        assert_eq!(
+            build_game_definition(&lines(&format!("7,4,99\n\n{}", board))),
+            Err(ParseError::NumberOnNoBoard { value: 99 })
+        );
     }
 
     #[test]
@@ -218,21 +474,103 @@ mod tests {
     #[test]
     fn check_board_won_for_horizontal() {
         let [board, _, _] = get_simple_case();
-        let (id, steps, score) = run_board(board, &vec![8_i16, 2, 23, 4, 24]);
+        let result = BingoGame::new(vec![board], vec![8_i16, 2, 23, 4, 24]).next();
 
-        assert_eq!(id, 0);
-        assert_eq!(steps, 5);
-        assert_eq!(score, 24 * 239);
+        assert_eq!(result, Some((0, 5, 24 * 239)));
     }
 
     #[test]
     fn check_board_won_for_vertical() {
         let [_, board, _] = get_simple_case();
-        let (id, steps, score) = run_board(board, &vec![15_i16, 18, 100, 8, 11, 21]);
+        let result = BingoGame::new(vec![board], vec![15_i16, 18, 100, 8, 11, 21]).next();
+
+        assert_eq!(result, Some((1, 6, 21 * 251)));
+    }
+
+    #[test]
+    fn check_board_display() {
+        let [mut board, _, _] = get_simple_case();
+        for number_drawn in [17, 8, 2, 23, 4, 24, 0] {
+            board.number_drawn(number_drawn);
+        }
+
+        assert_eq!(
+            board.to_string(),
+            [
+                " 22  13 [17] 11   0",
+                "[ 8][ 2][23][ 4][24]",
+                " 21   9  14  16   7",
+                "  6  10   3  18   5",
+                "  1  12  20  15  19",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn check_ranking_output() {
+        let results = vec![(2, 12, 4512), (0, 14, 2192)];
+
+        assert_eq!(
+            ranking_csv(&results, &[1]),
+            "rank,board,moves,score\n1,2,12,4512\n2,0,14,2192\n3,1,,\n"
+        );
+        assert_eq!(
+            ranking_json(&results, &[1]),
+            "[\n  {\"rank\": 1, \"board\": 2, \"moves\": 12, \"score\": 4512},\n  \
+             {\"rank\": 2, \"board\": 0, \"moves\": 14, \"score\": 2192},\n  \
+             {\"rank\": 3, \"board\": 1, \"moves\": null, \"score\": null}\n]\n"
+        );
+        assert_eq!(ranking_json(&[], &[]), "[]\n");
+    }
+
+    #[test]
+    fn check_play_in_parallel() {
+        let numbers_drawn = vec![
+            7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19,
+            3, 26, 1,
+        ];
+        for threads in 1..=4 {
+            let played = play(get_simple_case().into(), numbers_drawn.clone(), threads);
+
+            assert_eq!(
+                played.winners,
+                vec![(2, 12, 4512), (0, 14, 2192), (1, 15, 1924)]
+            );
+            assert_eq!(played.never_won, vec![]);
+            assert_eq!(
+                played.boards.iter().map(|b| b.id).collect::<Vec<_>>(),
+                vec![0, 1, 2]
+            );
+        }
+    }
+
+    #[test]
+    fn check_play_reports_boards_that_never_win() {
+        let numbers_drawn = vec![7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16];
+        let played = play(get_simple_case().into(), numbers_drawn, 2);
+
+        assert_eq!(played.winners, vec![(2, 12, 4512), (0, 14, 2192)]);
+        assert_eq!(played.never_won, vec![1]);
+    }
+
+    #[test]
+    fn check_board_not_won() {
+        let [board, _, _] = get_simple_case();
+        let result = BingoGame::new(vec![board], vec![8_i16, 2, 23, 4]).next();
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn check_game_winners_in_order() {
+        let numbers_drawn = vec![
+            7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19,
+            3, 26, 1,
+        ];
+        let winners = BingoGame::new(get_simple_case().into(), numbers_drawn).collect::<Vec<_>>();
 
-        assert_eq!(id, 1);
-        assert_eq!(steps, 6);
-        assert_eq!(score, 21 * 251);
+        assert_eq!(winners, vec![(2, 12, 4512), (0, 14, 2192), (1, 15, 1924)]);
     }
 
     fn get_simple_case() -> [BingoBoard; 3] {
@@ -0,0 +1,695 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+pub type Point = (i64, i64);
+pub type Line = (Point, Point);
+
+fn change_delta(x: &i64, y: &i64) -> i64 {
+    match x.cmp(y) {
+        std::cmp::Ordering::Less => 1,
+        std::cmp::Ordering::Greater => -1,
+        std::cmp::Ordering::Equal => 0,
+    }
+}
+
+/// Whether the line runs horizontally, vertically or at 45 degrees.
+fn is_supported_slope((start, end): &Line) -> bool {
+    let dx = i128::from(end.0) - i128::from(start.0);
+    let dy = i128::from(end.1) - i128::from(start.1);
+    dx == 0 || dy == 0 || dx.abs() == dy.abs()
+}
+
+pub fn to_points(line: &Line) -> Vec<Point> {
+    if is_supported_slope(line) {
+        to_points_stepped(line)
+    } else {
+        to_points_bresenham(line)
+    }
+}
+
+/// Rasterizes a line of any slope with Bresenham's algorithm.
+fn to_points_bresenham((start, end): &Line) -> Vec<Point> {
+    let dx = (i128::from(end.0) - i128::from(start.0)).abs();
+    let dy = -(i128::from(end.1) - i128::from(start.1)).abs();
+    let step_x = change_delta(&start.0, &end.0);
+    let step_y = change_delta(&start.1, &end.1);
+
+    let mut points = Vec::new();
+    let mut p = *start;
+    let mut error = dx + dy;
+    loop {
+        points.push(p);
+        if &p == end {
+            return points;
+        }
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            p.0 += step_x;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            p.1 += step_y;
+        }
+    }
+}
+
+fn to_points_stepped((start, end): &Line) -> Vec<Point> {
+    let mut points = Vec::new();
+    let change_delta_for_x = change_delta(&start.0, &end.0);
+    let change_delta_for_y = change_delta(&start.1, &end.1);
+
+    let mut p = *start;
+    while &p != end {
+        points.push(p);
+        p = (p.0 + change_delta_for_x, p.1 + change_delta_for_y)
+    }
+    points.push(p);
+    points
+}
+
+/// A line parsed from `x1,y1 -> x2,y2`.
+#[derive(Debug, PartialEq)]
+pub struct VentLine(pub Line);
+
+#[derive(Debug, PartialEq)]
+pub enum ParseReason {
+    /// The line held this many coordinates rather than 4.
+    WrongArity(usize),
+    NonNumericCoordinate(String),
+    /// The line is neither horizontal, vertical nor at 45 degrees.
+    UnsupportedSlope,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line_number: usize,
+    pub reason: ParseReason,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reason {
+            ParseReason::WrongArity(count) => write!(
+                f,
+                "Line {} has {} coordinates instead of 4",
+                self.line_number, count
+            ),
+            ParseReason::NonNumericCoordinate(value) => write!(
+                f,
+                "Line {} has a non-numeric coordinate {:?}",
+                self.line_number, value
+            ),
+            ParseReason::UnsupportedSlope => write!(
+                f,
+                "Line {} is not horizontal, vertical or at 45 degrees",
+                self.line_number
+            ),
+        }
+    }
+}
+
+impl FromStr for VentLine {
+    type Err = ParseReason;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers = s
+            .split("->")
+            .flat_map(|p| p.split(','))
+            .map(str::trim)
+            .map(|z| {
+                z.parse::<i64>()
+                    .map_err(|_| ParseReason::NonNumericCoordinate(z.to_owned()))
+            })
+            .collect::<Result<Vec<i64>, _>>()?;
+
+        match numbers[..] {
+            [x1, y1, x2, y2] => Ok(VentLine(((x1, y1), (x2, y2)))),
+            _ => Err(ParseReason::WrongArity(numbers.len())),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParseOptions {
+    /// Skip lines that fail to parse rather than stopping.
+    pub lenient: bool,
+    /// Accept lines at any slope, rasterizing them with Bresenham.
+    pub any_slope: bool,
+}
+
+pub fn parse_lines(lines: &[String], options: ParseOptions) -> Result<Vec<Line>, ParseError> {
+    let mut parsed = Vec::new();
+    for (line, line_number) in lines.iter().zip(1..) {
+        let vent_line = line.parse::<VentLine>().and_then(|VentLine(line)| {
+            if options.any_slope || is_supported_slope(&line) {
+                Ok(line)
+            } else {
+                Err(ParseReason::UnsupportedSlope)
+            }
+        });
+        match vent_line {
+            Ok(line) => parsed.push(line),
+            Err(_) if options.lenient => {}
+            Err(reason) => {
+                return Err(ParseError {
+                    line_number,
+                    reason,
+                })
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// Grids covering up to this many cells are counted densely.
+const DENSE_GRID_LIMIT: usize = 1 << 24;
+
+/// The points covered by more than one line.
+pub fn track_points(lines: &[Line]) -> Vec<Point> {
+    match bounding_box(lines) {
+        Some(bounds) if grid_size(bounds) <= DENSE_GRID_LIMIT as u128 => {
+            track_points_dense(lines, bounds)
+        }
+        _ => track_points_sparse(lines),
+    }
+}
+
+fn bounding_box(lines: &[Line]) -> Option<(Point, Point)> {
+    lines
+        .iter()
+        .flat_map(|(start, end)| [*start, *end])
+        .fold(None, |bounds, p| match bounds {
+            None => Some((p, p)),
+            Some((min, max)) => Some((
+                (min.0.min(p.0), min.1.min(p.1)),
+                (max.0.max(p.0), max.1.max(p.1)),
+            )),
+        })
+}
+
+fn grid_size((min, max): (Point, Point)) -> u128 {
+    let width = (i128::from(max.0) - i128::from(min.0) + 1) as u128;
+    let height = (i128::from(max.1) - i128::from(min.1) + 1) as u128;
+    width.saturating_mul(height)
+}
+
+fn track_points_dense(lines: &[Line], (min, max): (Point, Point)) -> Vec<Point> {
+    let width = (max.0 - min.0 + 1) as usize;
+    let index = |p: &Point| (p.1 - min.1) as usize * width + (p.0 - min.0) as usize;
+
+    let mut grid = vec![0_u16; grid_size((min, max)) as usize];
+    let mut overlaps = Vec::new();
+    for p in lines.iter().flat_map(to_points) {
+        let count = &mut grid[index(&p)];
+        *count = count.saturating_add(1);
+        if *count == 2 {
+            overlaps.push(p);
+        }
+    }
+    overlaps
+}
+
+fn track_points_sparse(lines: &[Line]) -> Vec<Point> {
+    let mut map = VentMap::new();
+    for line in lines {
+        map.add_line(*line);
+    }
+    map.counts
+        .into_iter()
+        .filter(|(_, x)| *x > 1)
+        .map(|(p, _)| p)
+        .collect()
+}
+
+/// How many lines cover each point, built up one line at a time.
+#[derive(Debug, Default)]
+pub struct VentMap {
+    counts: HashMap<Point, usize>,
+}
+
+impl VentMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_line(&mut self, line: Line) {
+        for p in to_points(&line) {
+            *self.counts.entry(p).or_insert(0) += 1;
+        }
+    }
+
+    /// The number of points covered by at least `min` lines.
+    pub fn overlap_count(&self, min: usize) -> usize {
+        self.counts.values().filter(|x| **x >= min).count()
+    }
+
+    /// The number of lines covering `(x, y)`.
+    pub fn cell(&self, x: i64, y: i64) -> usize {
+        self.counts.get(&(x, y)).copied().unwrap_or(0)
+    }
+}
+
+/// The four directions a line can run in. Each line within a family is
+/// identified by a key, and points along it by a parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Family {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+const FAMILIES: [Family; 4] = [
+    Family::Horizontal,
+    Family::Vertical,
+    Family::Diagonal,
+    Family::AntiDiagonal,
+];
+
+impl Family {
+    /// `(a, b)` such that every point on a line satisfies `a*x + b*y == key`.
+    fn coefficients(self) -> (i128, i128) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Diagonal => (1, -1),
+            Family::AntiDiagonal => (1, 1),
+        }
+    }
+
+    fn key(self, (x, y): (i128, i128)) -> i128 {
+        let (a, b) = self.coefficients();
+        a * x + b * y
+    }
+
+    fn param(self, (x, y): (i128, i128)) -> i128 {
+        match self {
+            Family::Vertical => y,
+            _ => x,
+        }
+    }
+}
+
+/// A line as the inclusive range `from..=to` it covers along its family line.
+#[derive(Debug)]
+struct Span {
+    family: Family,
+    key: i128,
+    from: i128,
+    to: i128,
+}
+
+impl Span {
+    fn new((start, end): &Line) -> Option<Span> {
+        let start = (i128::from(start.0), i128::from(start.1));
+        let end = (i128::from(end.0), i128::from(end.1));
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let family = match (dx, dy) {
+            (_, 0) => Family::Horizontal,
+            (0, _) => Family::Vertical,
+            _ if dx == dy => Family::Diagonal,
+            _ if dx == -dy => Family::AntiDiagonal,
+            _ => return None,
+        };
+        let (a, b) = (family.param(start), family.param(end));
+        Some(Span {
+            family,
+            key: family.key(start),
+            from: a.min(b),
+            to: a.max(b),
+        })
+    }
+
+    fn contains(&self, p: (i128, i128)) -> bool {
+        self.family.key(p) == self.key && (self.from..=self.to).contains(&self.family.param(p))
+    }
+
+    /// The lattice point where two spans from different families cross.
+    fn crossing(&self, other: &Span) -> Option<(i128, i128)> {
+        let (a1, b1) = self.family.coefficients();
+        let (a2, b2) = other.family.coefficients();
+        let det = a1 * b2 - a2 * b1;
+        let x = self.key * b2 - other.key * b1;
+        let y = a1 * other.key - a2 * self.key;
+        if det == 0 || x % det != 0 || y % det != 0 {
+            return None;
+        }
+        Some((x / det, y / det)).filter(|p| self.contains(*p) && other.contains(*p))
+    }
+}
+
+/// Counts the points covered by more than one line by comparing every pair of
+/// lines, without enumerating the points each line covers. Collinear overlaps
+/// are merged into ranges per family line, and single crossing points are
+/// counted unless a range already includes them.
+pub fn count_overlaps_analytic(lines: &[Line]) -> u128 {
+    let spans: Vec<Span> = lines.iter().filter_map(Span::new).collect();
+
+    let mut ranges: HashMap<(Family, i128), Vec<(i128, i128)>> = HashMap::new();
+    let mut crossings = std::collections::HashSet::new();
+    for (i, a) in spans.iter().enumerate() {
+        for b in &spans[i + 1..] {
+            if a.family != b.family {
+                crossings.extend(a.crossing(b));
+            } else if a.key == b.key && a.from.max(b.from) <= a.to.min(b.to) {
+                ranges
+                    .entry((a.family, a.key))
+                    .or_default()
+                    .push((a.from.max(b.from), a.to.min(b.to)));
+            }
+        }
+    }
+
+    let mut count: i128 = 0;
+    for merged in ranges.values_mut() {
+        merged.sort_unstable();
+        *merged = merged
+            .iter()
+            .fold(Vec::new(), |mut acc: Vec<(i128, i128)>, &(from, to)| {
+                match acc.last_mut() {
+                    Some(last) if from <= last.1 + 1 => last.1 = last.1.max(to),
+                    _ => acc.push((from, to)),
+                }
+                acc
+            });
+        count += merged.iter().map(|(from, to)| to - from + 1).sum::<i128>();
+    }
+
+    for p in crossings {
+        let covering = FAMILIES
+            .iter()
+            .filter(|family| {
+                let param = family.param(p);
+                ranges
+                    .get(&(**family, family.key(p)))
+                    .map(|merged| {
+                        let i = merged.partition_point(|(from, _)| *from <= param);
+                        i > 0 && merged[i - 1].1 >= param
+                    })
+                    .unwrap_or(false)
+            })
+            .count() as i128;
+        // A point inside several ranges has been counted once for each.
+        count += match covering {
+            0 => 1,
+            n => 1 - n,
+        };
+    }
+    count as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn check_parse_line() {
+        assert_eq!(
+            "0,9 -> 5,9".parse::<VentLine>(),
+            Ok(VentLine(((0, 9), (5, 9))))
+        );
+        assert_eq!(
+            "2,2 -> 2,1".parse::<VentLine>(),
+            Ok(VentLine(((2, 2), (2, 1))))
+        );
+        assert_eq!(
+            "40000,5000000000 -> 40002,5000000000".parse::<VentLine>(),
+            Ok(VentLine(((40000, 5000000000), (40002, 5000000000))))
+        );
+    }
+
+    #[test]
+    fn check_parse_errors() {
+        assert_eq!(
+            "0,9 -> 5".parse::<VentLine>(),
+            Err(ParseReason::WrongArity(3))
+        );
+        assert_eq!(
+            "0,9 -> 5,x".parse::<VentLine>(),
+            Err(ParseReason::NonNumericCoordinate("x".to_owned()))
+        );
+
+        let input = vec!["0,9 -> 5,9".to_owned(), "0,9 -> 5,9,1".to_owned()];
+        assert_eq!(
+            parse_lines(&input, ParseOptions::default()),
+            Err(ParseError {
+                line_number: 2,
+                reason: ParseReason::WrongArity(5)
+            })
+        );
+        let lenient = ParseOptions {
+            lenient: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(parse_lines(&input, lenient), Ok(vec![((0, 9), (5, 9))]));
+    }
+
+    #[test]
+    fn check_unsupported_slopes() {
+        let input = vec!["0,0 -> 4,2".to_owned()];
+        assert_eq!(
+            parse_lines(&input, ParseOptions::default()),
+            Err(ParseError {
+                line_number: 1,
+                reason: ParseReason::UnsupportedSlope
+            })
+        );
+
+        let any_slope = ParseOptions {
+            any_slope: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(parse_lines(&input, any_slope), Ok(vec![((0, 0), (4, 2))]));
+        assert_eq!(
+            to_points(&((0, 0), (4, 2))),
+            vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+        );
+        assert_eq!(
+            to_points(&((1, 5), (0, 1))),
+            vec![(1, 5), (1, 4), (0, 3), (0, 2), (0, 1)]
+        );
+    }
+
+    #[test]
+    fn check_bresenham_matches_stepped_points() {
+        for line in [
+            ((0, 9), (5, 9)),
+            ((7, 0), (7, 4)),
+            ((9, 4), (3, 4)),
+            ((6, 4), (2, 0)),
+            ((8, 0), (0, 8)),
+            ((3, 3), (3, 3)),
+        ] {
+            assert_eq!(to_points_bresenham(&line), to_points_stepped(&line));
+        }
+    }
+
+    #[test]
+    fn check_to_points() {
+        assert_eq!(
+            to_points(&((0, 9), (5, 9))),
+            vec!((0, 9), (1, 9), (2, 9), (3, 9), (4, 9), (5, 9))
+        );
+        assert_eq!(
+            to_points(&((7, 0), (7, 4))),
+            vec!((7, 0), (7, 1), (7, 2), (7, 3), (7, 4))
+        );
+
+        assert_eq!(
+            to_points(&((9, 4), (3, 4))),
+            vec!((9, 4), (8, 4), (7, 4), (6, 4), (5, 4), (4, 4), (3, 4))
+        );
+
+        assert_eq!(
+            to_points(&((6, 4), (2, 0))),
+            vec!((6, 4), (5, 3), (4, 2), (3, 1), (2, 0))
+        );
+    }
+
+    #[test]
+    fn check_track_points() {
+        let lines = [
+            ((0, 9), (5, 9)), // Line 1
+            ((9, 4), (3, 4)), // Line 2
+            ((2, 2), (2, 1)), // Line 3
+            ((7, 0), (7, 4)), // Line 4
+            ((0, 9), (2, 9)), // Line 5
+            ((3, 4), (1, 4)), // Line 6
+            ((8, 0), (0, 8)), // Line 7
+            ((6, 4), (2, 0)), // Line 8
+            ((0, 0), (8, 8)), // Line 9
+            ((5, 5), (8, 2)), // Line 10
+        ];
+        let total_matches = [
+            (7, 4), // From line: 4
+            (0, 9), // From line: 5
+            (1, 9), // From line: 5
+            (2, 9), // From line: 5
+            (3, 4), // From line: 6
+            (4, 4), // From line: 7
+            (7, 1), // From line: 7
+            (5, 3), // From line: 8
+            (6, 4), // From line: 8
+            (2, 2), // From line: 9
+            (5, 5), // From line: 10
+            (7, 3), // From line: 10
+        ];
+
+        iters_equal_anyorder(&track_points(&lines[..1]), &Vec::new());
+        iters_equal_anyorder(&track_points(&lines[..2]), &Vec::new());
+        iters_equal_anyorder(&track_points(&lines[..3]), &Vec::new());
+        iters_equal_anyorder(&track_points(&lines[..4]), &total_matches[..1]);
+        iters_equal_anyorder(&track_points(&lines[..5]), &total_matches[..4]);
+        iters_equal_anyorder(&track_points(&lines[..6]), &total_matches[..5]);
+        iters_equal_anyorder(&track_points(&lines[..7]), &total_matches[..7]);
+        iters_equal_anyorder(&track_points(&lines[..8]), &total_matches[..9]);
+        iters_equal_anyorder(&track_points(&lines[..9]), &total_matches[..10]);
+        iters_equal_anyorder(&track_points(&lines[..10]), &total_matches[..12]);
+    }
+
+    #[test]
+    fn check_vent_map() {
+        let mut map = VentMap::new();
+        map.add_line(((0, 9), (5, 9)));
+        map.add_line(((8, 0), (0, 8)));
+        assert_eq!(map.overlap_count(2), 0);
+        assert_eq!(map.cell(1, 9), 1);
+
+        map.add_line(((0, 9), (2, 9)));
+        map.add_line(((0, 0), (8, 8)));
+        assert_eq!(map.overlap_count(1), 23);
+        assert_eq!(map.overlap_count(2), 4);
+        assert_eq!(map.cell(1, 9), 2);
+        assert_eq!(map.cell(4, 4), 2);
+        assert_eq!(map.cell(9, 9), 0);
+    }
+
+    #[test]
+    fn check_dense_and_sparse_backends_agree() {
+        let lines = [
+            ((0, 9), (5, 9)),
+            ((8, 0), (0, 8)),
+            ((9, 4), (3, 4)),
+            ((2, 2), (2, 1)),
+            ((7, 0), (7, 4)),
+            ((6, 4), (2, 0)),
+            ((0, 9), (2, 9)),
+            ((3, 4), (1, 4)),
+            ((0, 0), (8, 8)),
+            ((5, 5), (8, 2)),
+        ];
+        let bounds = bounding_box(&lines).unwrap();
+
+        assert_eq!(bounds, ((0, 0), (9, 9)));
+        assert_eq!(track_points_dense(&lines, bounds).len(), 12);
+        iters_equal_anyorder(
+            &track_points_dense(&lines, bounds),
+            &track_points_sparse(&lines),
+        );
+    }
+
+    #[test]
+    fn check_dense_backend_with_negative_coordinates() {
+        let lines = [((-3, -1), (2, -1)), ((-2, -4), (-2, 4)), ((1, 1), (-3, -3))];
+        let bounds = bounding_box(&lines).unwrap();
+
+        iters_equal_anyorder(
+            &track_points_dense(&lines, bounds),
+            &[(-2, -1), (-1, -1), (-2, -2)],
+        );
+        iters_equal_anyorder(
+            &track_points_sparse(&lines),
+            &[(-2, -1), (-1, -1), (-2, -2)],
+        );
+    }
+
+    #[test]
+    fn check_track_points_with_large_coordinates() {
+        let lines = [
+            ((i64::MIN, 1), (i64::MIN + 4, 1)),
+            ((i64::MIN + 2, 0), (i64::MIN + 2, 3)),
+            ((i64::MAX, i64::MAX), (i64::MAX, i64::MAX - 1)),
+            ((i64::MAX, i64::MAX), (i64::MAX - 1, i64::MAX)),
+        ];
+
+        iters_equal_anyorder(
+            &track_points(&lines),
+            &[(i64::MIN + 2, 1), (i64::MAX, i64::MAX)],
+        );
+        iters_equal_anyorder(&track_points(&lines[..2]), &[(i64::MIN + 2, 1)]);
+    }
+
+    #[test]
+    fn check_count_overlaps_analytic() {
+        let lines = [
+            ((0, 9), (5, 9)),
+            ((8, 0), (0, 8)),
+            ((9, 4), (3, 4)),
+            ((2, 2), (2, 1)),
+            ((7, 0), (7, 4)),
+            ((6, 4), (2, 0)),
+            ((0, 9), (2, 9)),
+            ((3, 4), (1, 4)),
+            ((0, 0), (8, 8)),
+            ((5, 5), (8, 2)),
+        ];
+
+        assert_eq!(count_overlaps_analytic(&lines), 12);
+        for n in 0..lines.len() {
+            assert_eq!(
+                count_overlaps_analytic(&lines[..n]),
+                track_points(&lines[..n]).len() as u128
+            );
+        }
+        assert_eq!(
+            count_overlaps_analytic(&[((0, 0), (1_000_000_000, 0)), ((5, 0), (2_000_000_000, 0))]),
+            999_999_996
+        );
+    }
+
+    #[test]
+    fn check_count_overlaps_analytic_matches_enumeration() {
+        let mut seed: u64 = 42;
+        let mut next = |range: i64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) % range as u64) as i64
+        };
+        for _ in 0..50 {
+            let lines = (0..30)
+                .map(|_| {
+                    let start = (next(20) - 5, next(20) - 5);
+                    let length = next(12);
+                    let (dx, dy) = [
+                        (1, 0),
+                        (0, 1),
+                        (-1, 0),
+                        (0, -1),
+                        (1, 1),
+                        (1, -1),
+                        (-1, 1),
+                        (-1, -1),
+                        (0, 0),
+                    ][next(9) as usize];
+                    (start, (start.0 + dx * length, start.1 + dy * length))
+                })
+                .collect::<Vec<Line>>();
+
+            assert_eq!(
+                count_overlaps_analytic(&lines),
+                track_points(&lines).len() as u128,
+                "for lines {:?}",
+                lines
+            );
+        }
+    }
+
+    fn iters_equal_anyorder(actual: &[Point], expected: &[Point]) {
+        let m = HashSet::<_>::from_iter(actual.iter());
+        let n = HashSet::<_>::from_iter(expected.iter());
+        assert_eq!(m, n);
+    }
+}
@@ -0,0 +1,390 @@
+pub type Lanternfish = i8;
+type Sample = [usize; 9];
+type Matrix = [[usize; 9]; 9];
+
+pub fn get_lanternfish(s: String) -> Vec<Lanternfish> {
+    s.split(',').filter_map(|z| z.parse::<i8>().ok()).collect()
+}
+
+/// Ages a sample by one day, the reference `age_generations` is checked against.
+#[cfg(test)]
+fn age_generation(sample: Sample, _: usize) -> Sample {
+    [
+        sample[1],             // 0s
+        sample[2],             // 1s
+        sample[3],             // 2s
+        sample[4],             // 3s
+        sample[5],             // 4s
+        sample[6],             // 5s
+        sample[7] + sample[0], // 6s
+        sample[8],             // 7s
+        sample[0],             // 8s
+    ]
+}
+
+fn count_lanternfish(list: &[Lanternfish]) -> Sample {
+    [
+        list.iter().filter(|l| *l == &0).count(),
+        list.iter().filter(|l| *l == &1).count(),
+        list.iter().filter(|l| *l == &2).count(),
+        list.iter().filter(|l| *l == &3).count(),
+        list.iter().filter(|l| *l == &4).count(),
+        list.iter().filter(|l| *l == &5).count(),
+        list.iter().filter(|l| *l == &6).count(),
+        list.iter().filter(|l| *l == &7).count(),
+        list.iter().filter(|l| *l == &8).count(),
+    ]
+}
+
+/// The matrix that ages a sample by one day, as `age_generation` does.
+fn transition() -> Matrix {
+    let mut matrix = [[0; 9]; 9];
+    for (timer, row) in matrix.iter_mut().enumerate().take(8) {
+        row[timer + 1] = 1;
+    }
+    matrix[6][0] = 1;
+    matrix[8][0] = 1;
+    matrix
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Option<Matrix> {
+    let mut product = [[0; 9]; 9];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..9).try_fold(0_usize, |sum, k| {
+                sum.checked_add(a[i][k].checked_mul(b[k][j])?)
+            })?;
+        }
+    }
+    Some(product)
+}
+
+fn apply(matrix: &Matrix, sample: &Sample) -> Option<Sample> {
+    let mut aged = [0; 9];
+    for (timer, row) in aged.iter_mut().zip(matrix) {
+        *timer = row
+            .iter()
+            .zip(sample)
+            .try_fold(0_usize, |sum, (m, s)| sum.checked_add(m.checked_mul(*s)?))?;
+    }
+    Some(aged)
+}
+
+/// Ages a sample by any number of days in logarithmic time, by raising the
+/// transition matrix to that power. Returns None if a count overflows.
+fn age_generations(sample: Sample, days: u64) -> Option<Sample> {
+    if sample.iter().all(|n| *n == 0) {
+        return Some(sample);
+    }
+    let mut aged = sample;
+    let mut power = transition();
+    let mut remaining = days;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            aged = apply(&power, &aged)?;
+        }
+        remaining >>= 1;
+        if remaining > 0 {
+            power = multiply(&power, &power)?;
+        }
+    }
+    Some(aged)
+}
+
+fn total_sample(sample: Sample) -> u128 {
+    sample.iter().map(|n| *n as u128).sum::<u128>()
+}
+
+/// A school of lanternfish, counted by the days left on their timers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LanternfishPopulation {
+    sample: Sample,
+}
+
+impl LanternfishPopulation {
+    pub fn from_timers(timers: &[Lanternfish]) -> Self {
+        LanternfishPopulation {
+            sample: count_lanternfish(timers),
+        }
+    }
+
+    /// The population after another `days` days, or None if a count overflows.
+    pub fn step(&self, days: u64) -> Option<Self> {
+        Some(LanternfishPopulation {
+            sample: age_generations(self.sample, days)?,
+        })
+    }
+
+    pub fn total(&self) -> u128 {
+        total_sample(self.sample)
+    }
+
+    /// The number of fish with each timer value.
+    pub fn counts(&self) -> [usize; 9] {
+        self.sample
+    }
+
+    /// The first day on which the population is at least `target`, found by
+    /// doubling the day count until it is reached then binary searching.
+    /// None if the population never gets there or a count overflows first.
+    pub fn days_to_reach(&self, target: u128) -> Option<u64> {
+        if self.total() >= target {
+            return Some(0);
+        }
+        if self.total() == 0 {
+            return None;
+        }
+        // Overflowing counts are treated as reached, then checked at the end.
+        let reached = |days: u64| self.step(days).map(|p| p.total() >= target) != Some(false);
+
+        let (mut below, mut at_or_above) = (0, 1_u64);
+        while !reached(at_or_above) {
+            below = at_or_above;
+            at_or_above = at_or_above.checked_mul(2)?;
+        }
+        while at_or_above - below > 1 {
+            let middle = below + (at_or_above - below) / 2;
+            if reached(middle) {
+                at_or_above = middle;
+            } else {
+                below = middle;
+            }
+        }
+        self.step(at_or_above)
+            .filter(|p| p.total() >= target)
+            .map(|_| at_or_above)
+    }
+
+    /// This population followed by the population on each day after, until
+    /// a count overflows.
+    pub fn days(&self) -> impl Iterator<Item = LanternfishPopulation> {
+        std::iter::successors(Some(*self), |population| population.step(1))
+    }
+}
+
+impl FromIterator<Lanternfish> for LanternfishPopulation {
+    fn from_iter<I: IntoIterator<Item = Lanternfish>>(iter: I) -> Self {
+        Self::from_timers(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+/// Writes the population as `timer:count` pairs, skipping empty timers.
+impl std::fmt::Display for LanternfishPopulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pairs = self
+            .sample
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(timer, count)| format!("{}:{}", timer, count))
+            .collect::<Vec<_>>();
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StateError {
+    MalformedPair(String),
+    /// The timer is outside 0 to 8.
+    InvalidTimer(String),
+    InvalidCount(String),
+    CountOverflow {
+        timer: usize,
+    },
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::MalformedPair(pair) => {
+                write!(f, "Expected timer:count but found {:?}", pair)
+            }
+            StateError::InvalidTimer(timer) => write!(f, "Invalid timer {:?}", timer),
+            StateError::InvalidCount(count) => write!(f, "Invalid count {:?}", count),
+            StateError::CountOverflow { timer } => {
+                write!(f, "Too many fish with timer {}", timer)
+            }
+        }
+    }
+}
+
+/// Reads the `timer:count,timer:count` form written by `Display`. Repeated
+/// timers are added together.
+impl std::str::FromStr for LanternfishPopulation {
+    type Err = StateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sample = [0_usize; 9];
+        for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (timer, count) = pair
+                .split_once(':')
+                .ok_or_else(|| StateError::MalformedPair(pair.to_owned()))?;
+            let timer = timer
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|t| *t < 9)
+                .ok_or_else(|| StateError::InvalidTimer(timer.to_owned()))?;
+            let count = count
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| StateError::InvalidCount(count.to_owned()))?;
+            sample[timer] = sample[timer]
+                .checked_add(count)
+                .ok_or(StateError::CountOverflow { timer })?;
+        }
+        Ok(LanternfishPopulation { sample })
+    }
+}
+
+/// A CSV of the population on every day from 0 to `days`, optionally with
+/// the count for each timer.
+pub fn timeline_csv(initial: &LanternfishPopulation, days: u64, buckets: bool) -> Option<String> {
+    let mut csv = String::from("day,total");
+    if buckets {
+        (0..9).for_each(|timer| csv.push_str(&format!(",timer_{}", timer)));
+    }
+    csv.push('\n');
+
+    let mut populations = initial.days();
+    for day in 0..=days {
+        let population = populations.next()?;
+        csv.push_str(&format!("{},{}", day, population.total()));
+        if buckets {
+            for n in population.counts() {
+                csv.push_str(&format!(",{}", n));
+            }
+        }
+        csv.push('\n');
+    }
+    Some(csv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_get_lanternfish() {
+        assert_eq!(get_lanternfish("3,4,3,1,2".to_owned()), vec![3, 4, 3, 1, 2]);
+    }
+
+    #[test]
+    fn check_count_lanternfish() {
+        let s = count_lanternfish(&[3, 4, 3, 1, 2]);
+        assert_eq!(s, [0, 1, 1, 2, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn check_total_sample() {
+        assert_eq!(total_sample([0, 1, 1, 2, 1, 0, 0, 0, 0]), 5);
+    }
+
+    #[test]
+    fn check_age_generation() {
+        let initial = [
+            [0, 1, 1, 2, 1, 0, 0, 0, 0],
+            [1, 1, 2, 1, 0, 0, 0, 0, 0],
+            [1, 2, 1, 0, 0, 0, 1, 0, 1],
+            [2, 1, 0, 0, 0, 1, 1, 1, 1],
+            [1, 0, 0, 0, 1, 1, 3, 1, 2],
+        ];
+        assert_eq!(age_generation(initial[0], 0), initial[1]);
+        assert_eq!(age_generation(initial[1], 1), initial[2]);
+        assert_eq!(age_generation(initial[2], 2), initial[3]);
+        assert_eq!(age_generation(initial[3], 3), initial[4]);
+    }
+
+    #[test]
+    fn check_age_generations_matches_iteration() {
+        let initial = count_lanternfish(&[3, 4, 3, 1, 2]);
+        for days in 0..=300 {
+            let iterated = (0..days as usize).fold(initial, age_generation);
+            assert_eq!(age_generations(initial, days), Some(iterated));
+        }
+        assert_eq!(
+            age_generations(initial, 256).map(total_sample),
+            Some(26984457539)
+        );
+        assert_eq!(age_generations(initial, 1_000_000), None);
+        assert_eq!(age_generations([0; 9], 1_000_000), Some([0; 9]));
+    }
+
+    #[test]
+    fn check_population() {
+        let population: LanternfishPopulation = vec![3, 4, 3, 1, 2].into_iter().collect();
+        assert_eq!(
+            population,
+            LanternfishPopulation::from_timers(&[3, 4, 3, 1, 2])
+        );
+        assert_eq!(population.total(), 5);
+        assert_eq!(population.step(18).map(|p| p.total()), Some(26));
+        assert_eq!(population.step(80).map(|p| p.total()), Some(5934));
+        assert_eq!(
+            population.days().nth(2).map(|p| p.counts()),
+            Some([1, 2, 1, 0, 0, 0, 1, 0, 1])
+        );
+        assert_eq!(population.step(1_000_000), None);
+    }
+
+    #[test]
+    fn check_days_to_reach() {
+        let population = LanternfishPopulation::from_timers(&[3, 4, 3, 1, 2]);
+        for target in [0, 5, 6, 26, 27, 5934, 1_000_000, 26984457539] {
+            let iterated = population.days().position(|p| p.total() >= target);
+            assert_eq!(population.days_to_reach(target), iterated.map(|d| d as u64));
+        }
+        assert_eq!(population.days_to_reach(u128::MAX), None);
+        assert_eq!(
+            LanternfishPopulation::from_timers(&[]).days_to_reach(1),
+            None
+        );
+    }
+
+    #[test]
+    fn check_state_round_trip() {
+        let population = LanternfishPopulation::from_timers(&[3, 4, 3, 1, 2]);
+        assert_eq!(population.to_string(), "1:1,2:1,3:2,4:1");
+        assert_eq!("1:1,2:1,3:2,4:1".parse(), Ok(population));
+        assert_eq!(" 3:1, 1:1,3:1 ,2:1,4:1,8:0".parse(), Ok(population));
+
+        let huge: LanternfishPopulation = "0:5000000000,6:7".parse().unwrap();
+        assert_eq!(huge.total(), 5000000007);
+        assert_eq!(
+            huge.step(1).map(|p| p.to_string()),
+            Some("5:7,6:5000000000,8:5000000000".to_owned())
+        );
+        assert_eq!("".parse(), Ok(LanternfishPopulation::from_timers(&[])));
+    }
+
+    #[test]
+    fn check_state_errors() {
+        let parse = |s: &str| s.parse::<LanternfishPopulation>();
+        assert_eq!(parse("3"), Err(StateError::MalformedPair("3".to_owned())));
+        assert_eq!(parse("9:1"), Err(StateError::InvalidTimer("9".to_owned())));
+        assert_eq!(parse("1:x"), Err(StateError::InvalidCount("x".to_owned())));
+        assert_eq!(
+            parse(&format!("2:{},2:1", usize::MAX)),
+            Err(StateError::CountOverflow { timer: 2 })
+        );
+    }
+
+    #[test]
+    fn check_timeline_csv() {
+        let initial = LanternfishPopulation::from_timers(&[3, 4, 3, 1, 2]);
+        assert_eq!(
+            timeline_csv(&initial, 3, false),
+            Some("day,total\n0,5\n1,5\n2,6\n3,7\n".to_owned())
+        );
+        assert_eq!(
+            timeline_csv(&initial, 1, true),
+            Some(
+                "day,total,timer_0,timer_1,timer_2,timer_3,timer_4,timer_5,timer_6,timer_7,timer_8\n\
+                 0,5,0,1,1,2,1,0,0,0,0\n\
+                 1,5,1,1,2,1,0,0,0,0,0\n"
+                    .to_owned()
+            )
+        );
+        assert_eq!(timeline_csv(&initial, 1_000_000, false), None);
+    }
+}
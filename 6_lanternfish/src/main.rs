@@ -1,94 +1,80 @@
+use lanternfish::{get_lanternfish, timeline_csv, LanternfishPopulation, StateError};
 use std::io::{BufRead, BufReader};
 
-type Lanternfish = i8;
-type Sample = [usize; 9];
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let days = args
+        .iter()
+        .find_map(|a| a.strip_prefix("--days="))
+        .map(|d| {
+            d.parse::<u64>()
+                .map_err(|_| format!("Invalid day count: {}", d))
+        })
+        .transpose()?;
+    let timeline = args
+        .iter()
+        .position(|a| a == "--timeline")
+        .map(|i| args.get(i + 1).ok_or("Missing path after --timeline"))
+        .transpose()?;
+    let target = args
+        .iter()
+        .position(|a| a == "--target")
+        .map(|i| {
+            let value = args.get(i + 1).ok_or("Missing population after --target")?;
+            value
+                .parse::<u128>()
+                .map_err(|_| format!("Invalid target population: {}", value))
+        })
+        .transpose()?;
 
-fn get_lanternfish(s: String) -> Vec<Lanternfish> {
-    s.split(",").filter_map(|z| z.parse::<i8>().ok()).collect()
-}
-
-fn age_generation(sample: Sample, _: usize) -> Sample {
-    [
-        sample[1],             // 0s
-        sample[2],             // 1s
-        sample[3],             // 2s
-        sample[4],             // 3s
-        sample[5],             // 4s
-        sample[6],             // 5s
-        sample[7] + sample[0], // 6s
-        sample[8],             // 7s
-        sample[0],             // 8s
-    ]
-}
-
-fn count_lanternfish(list: &Vec<Lanternfish>) -> Sample {
-    [
-        list.iter().filter(|l| *l == &0).count(),
-        list.iter().filter(|l| *l == &1).count(),
-        list.iter().filter(|l| *l == &2).count(),
-        list.iter().filter(|l| *l == &3).count(),
-        list.iter().filter(|l| *l == &4).count(),
-        list.iter().filter(|l| *l == &5).count(),
-        list.iter().filter(|l| *l == &6).count(),
-        list.iter().filter(|l| *l == &7).count(),
-        list.iter().filter(|l| *l == &8).count(),
-    ]
-}
-
-fn total_sample(sample: Sample) -> usize {
-    sample.iter().sum::<usize>()
-}
-
-fn main() {
-    let lanternfish: Vec<Lanternfish> = BufReader::new(std::io::stdin())
+    let input = BufReader::new(std::io::stdin())
         .lines()
-        .filter_map(Result::ok)
-        .flat_map(get_lanternfish)
-        .collect();
-
-    let after_18 = (0..18)
-        .into_iter()
-        .fold(count_lanternfish(&lanternfish), age_generation);
-    let after_80 = (18..80).into_iter().fold(after_18, age_generation);
-    let after_256 = (80..256).into_iter().fold(after_80, age_generation);
-
-    println!("Total after 18: {}", total_sample(after_18));
-    println!("Total after 80: {}", total_sample(after_80));
-    println!("Total after 256: {}", total_sample(after_256));
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+        .map_while(Result::ok)
+        .collect::<Vec<_>>();
+    // Pre-aggregated `timer:count` input, as written by --dump-state.
+    let population: LanternfishPopulation = if input.iter().any(|line| line.contains(':')) {
+        input
+            .join(",")
+            .parse()
+            .map_err(|err: StateError| err.to_string())?
+    } else {
+        input.into_iter().flat_map(get_lanternfish).collect()
+    };
 
-    #[test]
-    fn check_get_lanternfish() {
-        assert_eq!(get_lanternfish("3,4,3,1,2".to_owned()), vec![3, 4, 3, 1, 2]);
+    // Only the state is written, so that it can be fed back in to resume.
+    if args.iter().any(|a| a == "--dump-state") {
+        let day = days.unwrap_or(0);
+        let aged = population
+            .step(day)
+            .ok_or_else(|| format!("Population overflows within {} days", day))?;
+        println!("{}", aged);
+        return Ok(());
     }
 
-    #[test]
-    fn check_count_lanternfish() {
-        let s = count_lanternfish(&vec![3, 4, 3, 1, 2]);
-        assert_eq!(s, [0, 1, 1, 2, 1, 0, 0, 0, 0]);
+    let mut report_days = vec![18, 80, 256];
+    report_days.extend(days);
+    for day in report_days {
+        let aged = population
+            .step(day)
+            .ok_or_else(|| format!("Population overflows within {} days", day))?;
+        println!("Total after {}: {}", day, aged.total());
     }
 
-    #[test]
-    fn check_total_sample() {
-        assert_eq!(total_sample([0, 1, 1, 2, 1, 0, 0, 0, 0]), 5);
+    if let Some(target) = target {
+        match population.days_to_reach(target) {
+            Some(day) => println!("Population reaches {} on day {}", target, day),
+            None => println!(
+                "Population never reaches {} before the counts overflow",
+                target
+            ),
+        }
     }
 
-    #[test]
-    fn check_age_generation() {
-        let initial = [
-            [0, 1, 1, 2, 1, 0, 0, 0, 0],
-            [1, 1, 2, 1, 0, 0, 0, 0, 0],
-            [1, 2, 1, 0, 0, 0, 1, 0, 1],
-            [2, 1, 0, 0, 0, 1, 1, 1, 1],
-            [1, 0, 0, 0, 1, 1, 3, 1, 2],
-        ];
-        assert_eq!(age_generation(initial[0], 0), initial[1]);
-        assert_eq!(age_generation(initial[1], 1), initial[2]);
-        assert_eq!(age_generation(initial[2], 2), initial[3]);
-        assert_eq!(age_generation(initial[3], 3), initial[4]);
+    if let Some(path) = timeline {
+        let last_day = days.unwrap_or(256);
+        let csv = timeline_csv(&population, last_day, args.iter().any(|a| a == "--buckets"))
+            .ok_or_else(|| format!("Population overflows within {} days", last_day))?;
+        std::fs::write(path, csv).map_err(|err| err.to_string())?;
     }
+    Ok(())
 }
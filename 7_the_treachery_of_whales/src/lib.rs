@@ -0,0 +1,378 @@
+pub type Position = i64;
+
+/// The fuel a crab burns to move a distance, or None if it overflows.
+pub trait CostModel {
+    fn cost(&self, distance: i64) -> Option<i64>;
+
+    /// Whether the cost is convex in the distance, which makes the total
+    /// fuel convex in the target position.
+    fn is_convex(&self) -> bool {
+        false
+    }
+}
+
+/// One unit of fuel per step.
+pub struct UnitCost;
+
+/// Each step costs one more than the last.
+pub struct TriangularCost;
+
+/// The square of the distance.
+pub struct QuadraticCost;
+
+/// Another model, but never more than `cap`.
+pub struct CappedCost<M: CostModel> {
+    pub model: M,
+    pub cap: i64,
+}
+
+impl CostModel for UnitCost {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        Some(distance)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl CostModel for TriangularCost {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        // Halve whichever factor is even so the product is not overflowed
+        // any sooner than the result.
+        if distance % 2 == 0 {
+            (distance / 2).checked_mul(distance.checked_add(1)?)
+        } else {
+            distance.checked_mul(distance.checked_add(1)? / 2)
+        }
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl CostModel for QuadraticCost {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        distance.checked_mul(distance)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl<M: CostModel> CostModel for CappedCost<M> {
+    fn cost(&self, distance: i64) -> Option<i64> {
+        Some(
+            self.model
+                .cost(distance)
+                .map_or(self.cap, |c| c.min(self.cap)),
+        )
+    }
+}
+
+pub fn get_positions(s: String) -> Vec<Position> {
+    s.split(',').filter_map(|z| z.parse::<i64>().ok()).collect()
+}
+
+/// The total fuel to align every crab at `at`, or None if it overflows.
+fn fuel_at(positions: &[Position], at: Position, model: &dyn CostModel) -> Option<i64> {
+    positions.iter().try_fold(0_i64, |total, p| {
+        let distance = p.checked_sub(at)?.checked_abs()?;
+        total.checked_add(model.cost(distance)?)
+    })
+}
+
+/// The least fuel needed to align on any position between the outermost
+/// crabs, which holds the optimum for any cost that never falls as the
+/// distance grows. Positions whose total overflows cannot be the least.
+fn min_fuel(positions: &[Position], model: &dyn CostModel) -> Option<i64> {
+    let min_range = *positions.iter().min()?;
+    let max_range = *positions.iter().max()?;
+    (min_range..=max_range)
+        .filter_map(|at| fuel_at(positions, at, model))
+        .min()
+}
+
+/// Finds the least fuel by ternary search, narrowing the range by a third at
+/// each step, which only holds for convex models. Overflowing totals are
+/// treated as larger than any other.
+fn min_fuel_ternary(positions: &[Position], model: &dyn CostModel) -> Option<i64> {
+    let fuel = |at| fuel_at(positions, at, model).map_or(i128::MAX, i128::from);
+    let mut low = *positions.iter().min()?;
+    let mut high = *positions.iter().max()?;
+    while i128::from(high) - i128::from(low) > 2 {
+        let third = ((i128::from(high) - i128::from(low)) / 3) as i64;
+        let (left, right) = (low + third, high - third);
+        match fuel(left).cmp(&fuel(right)) {
+            std::cmp::Ordering::Less => high = right - 1,
+            std::cmp::Ordering::Greater => low = left + 1,
+            std::cmp::Ordering::Equal => (low, high) = (left, right),
+        }
+    }
+    (low..=high)
+        .filter_map(|at| fuel_at(positions, at, model))
+        .min()
+}
+
+/// Uses ternary search for convex models, otherwise scans every position.
+fn min_fuel_fast(positions: &[Position], model: &dyn CostModel) -> Option<i64> {
+    if model.is_convex() {
+        min_fuel_ternary(positions, model)
+    } else {
+        min_fuel(positions, model)
+    }
+}
+
+/// The unit cost is minimised at the median position.
+fn min_unit_distance(positions: &[Position]) -> Option<i64> {
+    let mut sorted = positions.to_vec();
+    sorted.sort_unstable();
+    let median = sorted.get(sorted.len() / 2)?;
+    fuel_at(positions, *median, &UnitCost)
+}
+
+/// The triangular cost is minimised within half a step of the mean, so only
+/// the integers either side of it need checking.
+fn min_triangular_distance(positions: &[Position]) -> Option<i64> {
+    if positions.is_empty() {
+        return None;
+    }
+    let sum: i128 = positions.iter().map(|p| i128::from(*p)).sum();
+    let mean = sum.div_euclid(positions.len() as i128) as Position;
+    [Some(mean), mean.checked_add(1)]
+        .iter()
+        .flatten()
+        .filter_map(|at| fuel_at(positions, *at, &TriangularCost))
+        .min()
+}
+
+/// The crabs to align, and the fuel needed to align them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrabSwarm {
+    positions: Vec<Position>,
+}
+
+impl CrabSwarm {
+    pub fn from_positions(positions: Vec<Position>) -> Self {
+        CrabSwarm { positions }
+    }
+
+    /// The total fuel to align at `position`, or None if it overflows.
+    pub fn fuel_at(&self, position: Position, model: &dyn CostModel) -> Option<i64> {
+        fuel_at(&self.positions, position, model)
+    }
+
+    /// The least fuel to align, by ternary search for convex models and by
+    /// scanning otherwise.
+    pub fn min_fuel(&self, model: &dyn CostModel) -> Option<i64> {
+        min_fuel_fast(&self.positions, model)
+    }
+
+    pub fn min_fuel_scan(&self, model: &dyn CostModel) -> Option<i64> {
+        min_fuel(&self.positions, model)
+    }
+
+    pub fn min_fuel_ternary(&self, model: &dyn CostModel) -> Option<i64> {
+        min_fuel_ternary(&self.positions, model)
+    }
+
+    /// The least unit fuel, from the median.
+    pub fn min_unit_fuel(&self) -> Option<i64> {
+        min_unit_distance(&self.positions)
+    }
+
+    /// The least triangular fuel, from around the mean.
+    pub fn min_triangular_fuel(&self) -> Option<i64> {
+        min_triangular_distance(&self.positions)
+    }
+
+    /// The fuel to align at each position between the outermost crabs.
+    pub fn fuel_curve<'a>(
+        &'a self,
+        model: &'a dyn CostModel,
+    ) -> impl Iterator<Item = (Position, Option<i64>)> + 'a {
+        let range = self
+            .positions
+            .iter()
+            .min()
+            .zip(self.positions.iter().max())
+            .map(|(min, max)| *min..=*max);
+        range
+            .into_iter()
+            .flatten()
+            .map(move |at| (at, self.fuel_at(at, model)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_get_positions() {
+        assert_eq!(
+            get_positions("16,1,2,0,4,2,7,1,2,14".to_owned()),
+            vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]
+        );
+    }
+
+    #[test]
+    fn check_fuel_at() {
+        let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        let expectations = [
+            (0, 49, 290),
+            (1, 41, 242),
+            (2, 37, 206),
+            (3, 39, 183),
+            (4, 41, 170),
+            (5, 45, 168),
+            (6, 49, 176),
+            (7, 53, 194),
+            (8, 59, 223),
+            (9, 65, 262),
+            (10, 71, 311),
+            (11, 77, 370),
+            (12, 83, 439),
+            (13, 89, 518),
+            (14, 95, 607),
+            (15, 103, 707),
+            (16, 111, 817),
+        ];
+
+        for (at, unit, triangular) in expectations {
+            assert_eq!(fuel_at(&positions, at, &UnitCost), Some(unit));
+            assert_eq!(fuel_at(&positions, at, &TriangularCost), Some(triangular));
+        }
+    }
+
+    #[test]
+    fn check_closed_form_matches_scan() {
+        let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(min_unit_distance(&positions), Some(37));
+        assert_eq!(min_triangular_distance(&positions), Some(168));
+        assert_eq!(
+            min_fuel(&positions, &UnitCost).zip(min_fuel(&positions, &TriangularCost)),
+            Some((37, 168))
+        );
+
+        let mut seed = 7_u32;
+        for size in 1..40 {
+            let positions: Vec<Position> = (0..size)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    ((seed >> 16) % 200) as Position - 50
+                })
+                .collect();
+            let scanned =
+                min_fuel(&positions, &UnitCost).zip(min_fuel(&positions, &TriangularCost));
+            assert_eq!(min_unit_distance(&positions), scanned.map(|s| s.0));
+            assert_eq!(min_triangular_distance(&positions), scanned.map(|s| s.1));
+        }
+        assert_eq!(min_unit_distance(&[]), None);
+        assert_eq!(min_triangular_distance(&[]), None);
+    }
+
+    #[test]
+    fn check_cost_models() {
+        let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(min_fuel(&positions, &UnitCost), Some(37));
+        assert_eq!(min_fuel(&positions, &TriangularCost), Some(168));
+        assert_eq!(fuel_at(&positions, 2, &QuadraticCost), Some(375));
+        assert_eq!(min_fuel(&positions, &QuadraticCost), Some(291));
+
+        let capped = CappedCost {
+            model: TriangularCost,
+            cap: 10,
+        };
+        assert_eq!(capped.cost(3), Some(6));
+        assert_eq!(capped.cost(4), Some(10));
+        assert_eq!(capped.cost(i64::MAX), Some(10));
+        assert_eq!(fuel_at(&positions, 2, &capped), Some(38));
+        assert_eq!(min_fuel(&[], &capped), None);
+    }
+
+    #[test]
+    fn check_large_positions() {
+        let positions = [0, 4_000_000_000, 4_000_000_002];
+        assert_eq!(min_unit_distance(&positions), Some(4_000_000_002));
+        assert_eq!(
+            TriangularCost.cost(4_000_000_000),
+            Some(8_000_000_002_000_000_000)
+        );
+        assert_eq!(
+            min_triangular_distance(&positions),
+            fuel_at(&positions, 2_666_666_667, &TriangularCost)
+        );
+        assert_eq!(TriangularCost.cost(i64::MAX), None);
+        assert_eq!(fuel_at(&[i64::MIN, i64::MAX], 0, &UnitCost), None);
+        assert_eq!(min_triangular_distance(&[i64::MAX, i64::MAX]), Some(0));
+    }
+
+    #[test]
+    fn check_ternary_matches_scan() {
+        let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(min_fuel_ternary(&positions, &UnitCost), Some(37));
+        assert_eq!(min_fuel_ternary(&positions, &TriangularCost), Some(168));
+        assert_eq!(min_fuel_ternary(&positions, &QuadraticCost), Some(291));
+        assert_eq!(min_fuel_ternary(&[], &UnitCost), None);
+        assert_eq!(
+            min_fuel_ternary(&[i64::MIN, i64::MAX, i64::MAX], &UnitCost),
+            None
+        );
+        assert_eq!(
+            min_fuel_ternary(&[i64::MIN + 1, 0, 5], &UnitCost),
+            fuel_at(&[i64::MIN + 1, 0, 5], 0, &UnitCost)
+        );
+
+        let mut seed = 11_u32;
+        for size in 1..40 {
+            let positions: Vec<Position> = (0..size)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    ((seed >> 16) % 500) as Position - 100
+                })
+                .collect();
+            let models: [&dyn CostModel; 3] = [&UnitCost, &TriangularCost, &QuadraticCost];
+            for model in models {
+                assert_eq!(
+                    min_fuel_ternary(&positions, model),
+                    min_fuel(&positions, model)
+                );
+            }
+        }
+
+        let capped = CappedCost {
+            model: UnitCost,
+            cap: 3,
+        };
+        assert!(!capped.is_convex());
+        assert_eq!(
+            min_fuel_fast(&positions, &capped),
+            min_fuel(&positions, &capped)
+        );
+    }
+
+    #[test]
+    fn check_crab_swarm() {
+        let swarm = CrabSwarm::from_positions(vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+        assert_eq!(swarm.fuel_at(2, &UnitCost), Some(37));
+        assert_eq!(swarm.min_fuel(&TriangularCost), Some(168));
+        assert_eq!(swarm.min_fuel_scan(&UnitCost), swarm.min_unit_fuel());
+        assert_eq!(
+            swarm.min_fuel_ternary(&TriangularCost),
+            swarm.min_triangular_fuel()
+        );
+
+        let curve = swarm.fuel_curve(&UnitCost).collect::<Vec<_>>();
+        assert_eq!(curve.len(), 17);
+        assert_eq!(curve[..3], [(0, Some(49)), (1, Some(41)), (2, Some(37))]);
+        assert_eq!(
+            CrabSwarm::from_positions(vec![])
+                .fuel_curve(&UnitCost)
+                .count(),
+            0
+        );
+    }
+}
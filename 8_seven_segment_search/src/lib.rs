@@ -0,0 +1,661 @@
+use std::collections::{HashMap, HashSet};
+
+/// What `solve` deduced about an entry's wiring.
+#[derive(Debug)]
+pub struct WiringSolution {
+    /// The display segment each wire drives.
+    pub wiring: HashMap<Segment, Segment>,
+    /// The signal pattern for each digit, indexed by the digit.
+    pub patterns: [SignalPattern; 10],
+}
+
+impl WiringSolution {
+    /// Builds the pattern for each digit from the segments it lights.
+    pub fn from_wiring(wiring: HashMap<Segment, Segment>) -> Self {
+        let patterns = DIGIT_SEGMENTS.map(|digit| {
+            SignalPattern(
+                wiring
+                    .iter()
+                    .filter(|(_, segment)| digit.contains(segment.to_char()))
+                    .map(|(wire, _)| *wire)
+                    .collect(),
+            )
+        });
+        WiringSolution { wiring, patterns }
+    }
+
+    pub fn digit(&self, pattern: &SignalPattern) -> Option<i8> {
+        self.patterns
+            .iter()
+            .position(|p| p.0 == pattern.0)
+            .map(|digit| digit as i8)
+    }
+
+    pub fn digits(&self, output_values: &[SignalPattern; 4]) -> Result<[i8; 4], DecodeStep> {
+        let mut digits = [0; 4];
+        for (digit, pattern) in digits.iter_mut().zip(output_values) {
+            *digit = self
+                .digit(pattern)
+                .ok_or_else(|| DecodeStep::UnknownOutput(pattern.to_string()))?;
+        }
+        Ok(digits)
+    }
+}
+
+/// Lists the wiring as `wire->segment` pairs in wire order.
+impl std::fmt::Display for WiringSolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut wires = self.wiring.iter().collect::<Vec<_>>();
+        wires.sort();
+        let pairs = wires
+            .iter()
+            .map(|(wire, segment)| format!("{}->{}", wire.to_char(), segment.to_char()))
+            .collect::<Vec<_>>();
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+/// Draws output values as seven-segment digits. Each lit segment shows the
+/// wire that drives it, as in the puzzle's diagrams.
+pub fn render(solution: &WiringSolution, output_values: &[SignalPattern]) -> String {
+    let lit = output_values
+        .iter()
+        .map(|pattern| {
+            pattern
+                .0
+                .iter()
+                .filter_map(|wire| Some((*solution.wiring.get(wire)?, wire.to_char())))
+                .collect::<HashMap<Segment, char>>()
+        })
+        .collect::<Vec<_>>();
+    let show = |digit: &HashMap<Segment, char>, segment| *digit.get(&segment).unwrap_or(&'.');
+
+    // Each row is a horizontal segment, or a pair of vertical ones.
+    let rows = [
+        (Segment::A, None),
+        (Segment::B, Some(Segment::C)),
+        (Segment::B, Some(Segment::C)),
+        (Segment::D, None),
+        (Segment::E, Some(Segment::F)),
+        (Segment::E, Some(Segment::F)),
+        (Segment::G, None),
+    ];
+    rows.iter()
+        .map(|(left, right)| {
+            let cells = lit
+                .iter()
+                .map(|digit| match right {
+                    None => format!(" {} ", show(digit, *left).to_string().repeat(4)),
+                    Some(right) => format!("{}    {}", show(digit, *left), show(digit, *right)),
+                })
+                .collect::<Vec<_>>();
+            format!("{}\n", cells.join("  ").trim_end())
+        })
+        .collect()
+}
+
+/// One line of `--details` output: the decoded value then the wiring.
+pub fn entry_details(line_number: usize, digits: [i8; 4], solution: &WiringSolution) -> String {
+    let value = digits.iter().map(|d| d.to_string()).collect::<String>();
+    format!("Line {}: {} wiring {}", line_number, value, solution)
+}
+
+fn single_segment<'a>(
+    mut segments: impl Iterator<Item = &'a Segment>,
+    segment: Segment,
+) -> Result<Segment, DecodeStep> {
+    match (segments.next(), segments.next()) {
+        (Some(wire), None) => Ok(*wire),
+        _ => Err(DecodeStep::Segment(segment)),
+    }
+}
+
+fn solve(entry: &Entry) -> Result<WiringSolution, DecodeStep> {
+    let mut digit_1 = None;
+    let mut digit_4 = None;
+    let mut digit_7 = None;
+    let mut digit_8 = None;
+    let mut len_5_digits = Vec::new();
+    let mut len_6_digits = Vec::new();
+    for signal_pattern in entry.signal_patterns.iter().cloned() {
+        match signal_pattern.0.len() {
+            2 if digit_1.is_none() => digit_1 = Some(signal_pattern),
+            3 if digit_7.is_none() => digit_7 = Some(signal_pattern),
+            4 if digit_4.is_none() => digit_4 = Some(signal_pattern),
+            7 if digit_8.is_none() => digit_8 = Some(signal_pattern),
+            5 => len_5_digits.push(signal_pattern),
+            6 => len_6_digits.push(signal_pattern),
+            length => return Err(DecodeStep::UnexpectedPattern { length }),
+        }
+    }
+    let digit_1 = digit_1.ok_or(DecodeStep::MissingDigit(1))?;
+    let digit_4 = digit_4.ok_or(DecodeStep::MissingDigit(4))?;
+    let digit_7 = digit_7.ok_or(DecodeStep::MissingDigit(7))?;
+    let digit_8 = digit_8.ok_or(DecodeStep::MissingDigit(8))?;
+    let [b_segment, d_segment] = match digit_4.0.difference(&digit_1.0).collect::<Vec<&Segment>>()[..]
+    {
+        [x, y] => {
+            if len_6_digits.iter().all(|digit| digit.0.contains(x)) {
+                [*x, *y]
+            } else {
+                [*y, *x]
+            }
+        }
+        _ => return Err(DecodeStep::SegmentPair(Segment::B, Segment::D)),
+    };
+    let (digit_5, digit_2_or_3): (Vec<SignalPattern>, Vec<SignalPattern>) = len_5_digits
+        .into_iter()
+        .partition(|digit| digit.0.contains(&b_segment));
+    let (digit_6_or_9, digit_0): (Vec<SignalPattern>, Vec<SignalPattern>) = len_6_digits
+        .into_iter()
+        .partition(|digit| digit.0.contains(&d_segment));
+    let digit_5 = digit_5.first().ok_or(DecodeStep::MissingDigit(5))?;
+    let digit_0 = digit_0.first().ok_or(DecodeStep::MissingDigit(0))?;
+    let [c_segment, e_segment] = match digit_0.0.difference(&digit_5.0).collect::<Vec<&Segment>>()[..]
+    {
+        [x, y] => {
+            if digit_2_or_3.iter().all(|digit| digit.0.contains(x)) {
+                [*x, *y]
+            } else {
+                [*y, *x]
+            }
+        }
+        _ => return Err(DecodeStep::SegmentPair(Segment::C, Segment::E)),
+    };
+    let (digit_2, digit_3): (Vec<SignalPattern>, Vec<SignalPattern>) = digit_2_or_3
+        .into_iter()
+        .partition(|digit| digit.0.contains(&e_segment));
+    let (digit_9, digit_6): (Vec<SignalPattern>, Vec<SignalPattern>) = digit_6_or_9
+        .into_iter()
+        .partition(|digit| digit.0.contains(&c_segment));
+    let digit_2 = digit_2.first().ok_or(DecodeStep::MissingDigit(2))?;
+    let digit_3 = digit_3.first().ok_or(DecodeStep::MissingDigit(3))?;
+    let digit_6 = digit_6.first().ok_or(DecodeStep::MissingDigit(6))?;
+    let digit_9 = digit_9.first().ok_or(DecodeStep::MissingDigit(9))?;
+
+    let a_segment = single_segment(digit_7.0.difference(&digit_1.0), Segment::A)?;
+    let f_segment = single_segment(digit_1.0.iter().filter(|s| **s != c_segment), Segment::F)?;
+    let known = [
+        a_segment, b_segment, c_segment, d_segment, e_segment, f_segment,
+    ];
+    let g_segment = single_segment(digit_8.0.iter().filter(|s| !known.contains(s)), Segment::G)?;
+
+    let wiring = HashMap::from([
+        (a_segment, Segment::A),
+        (b_segment, Segment::B),
+        (c_segment, Segment::C),
+        (d_segment, Segment::D),
+        (e_segment, Segment::E),
+        (f_segment, Segment::F),
+        (g_segment, Segment::G),
+    ]);
+    Ok(WiringSolution {
+        wiring,
+        patterns: [
+            digit_0.clone(),
+            digit_1,
+            digit_2.clone(),
+            digit_3.clone(),
+            digit_4,
+            digit_5.clone(),
+            digit_6.clone(),
+            digit_7,
+            digit_8,
+            digit_9.clone(),
+        ],
+    })
+}
+
+/// The segments lit for each digit on a correctly wired display.
+const DIGIT_SEGMENTS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+const SEGMENTS: [Segment; 7] = [
+    Segment::A,
+    Segment::B,
+    Segment::C,
+    Segment::D,
+    Segment::E,
+    Segment::F,
+    Segment::G,
+];
+
+/// Every way of assigning the seven wires to the seven segments, indexed by
+/// wire.
+fn wirings() -> Vec<Vec<Segment>> {
+    (0..SEGMENTS.len()).fold(vec![Vec::new()], |partials, _| {
+        partials
+            .into_iter()
+            .flat_map(|partial: Vec<Segment>| {
+                SEGMENTS
+                    .iter()
+                    .filter(|s| !partial.contains(s))
+                    .map(|s| {
+                        let mut next = partial.clone();
+                        next.push(*s);
+                        next
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    })
+}
+
+fn segment_mask<'a>(segments: impl Iterator<Item = &'a Segment>) -> u8 {
+    segments.fold(0, |mask, s| mask | 1 << *s as u8)
+}
+
+/// Solves an entry by trying all 5040 wirings for one under which every
+/// signal pattern lights a digit. Slower than `solve`, but makes no
+/// assumptions beyond the digit shapes, so copes with fewer than ten
+/// patterns as long as only one wiring fits them.
+fn solve_by_search(entry: &Entry) -> Result<WiringSolution, DecodeStep> {
+    let digit_masks = DIGIT_SEGMENTS.map(|digit| {
+        digit
+            .bytes()
+            .fold(0_u8, |mask, segment| mask | 1 << (segment - b'a'))
+    });
+    let mut fitting = wirings().into_iter().filter(|wiring| {
+        entry.signal_patterns.iter().all(|pattern| {
+            let mask = segment_mask(pattern.0.iter().map(|wire| &wiring[*wire as usize]));
+            digit_masks.contains(&mask)
+        })
+    });
+    let wiring = fitting.next().ok_or(DecodeStep::NoWiring)?;
+    if fitting.next().is_some() {
+        return Err(DecodeStep::Ambiguous);
+    }
+    Ok(WiringSolution::from_wiring(
+        SEGMENTS.iter().copied().zip(wiring).collect(),
+    ))
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Segment {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+}
+
+impl Segment {
+    pub fn from_char(s: char) -> Result<Self, String> {
+        match s {
+            'a' => Ok(Segment::A),
+            'b' => Ok(Segment::B),
+            'c' => Ok(Segment::C),
+            'd' => Ok(Segment::D),
+            'e' => Ok(Segment::E),
+            'f' => Ok(Segment::F),
+            'g' => Ok(Segment::G),
+            _ => Err(format!("Unrecognised segment {}", s)),
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Segment::A => 'a',
+            Segment::B => 'b',
+            Segment::C => 'c',
+            Segment::D => 'd',
+            Segment::E => 'e',
+            Segment::F => 'f',
+            Segment::G => 'g',
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SignalPattern(pub HashSet<Segment>);
+
+impl std::str::FromStr for SignalPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .map(Segment::from_char)
+            .collect::<Result<Vec<Segment>, _>>()
+            .map(|segments| {
+                let pattern: HashSet<Segment> = HashSet::from_iter(segments);
+                SignalPattern(pattern)
+            })
+    }
+}
+
+/// Writes the pattern's segments in alphabetical order.
+impl std::fmt::Display for SignalPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut segments = self.0.iter().collect::<Vec<_>>();
+        segments.sort();
+        let letters = segments.iter().map(|s| s.to_char()).collect::<String>();
+        write!(f, "{}", letters)
+    }
+}
+
+#[derive(Debug)]
+pub struct Entry {
+    /// Up to ten patterns, ideally one for each digit.
+    pub signal_patterns: Vec<SignalPattern>,
+    pub output_values: [SignalPattern; 4],
+}
+
+impl std::str::FromStr for Entry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || "Invalid formated line supplied".to_owned();
+        let (patterns, outputs) = s.split_once(" | ").ok_or_else(invalid)?;
+        let signal_patterns = patterns
+            .split(' ')
+            .map(str::parse)
+            .collect::<Result<Vec<SignalPattern>, _>>()?;
+        if signal_patterns.len() > 10 {
+            return Err(invalid());
+        }
+        let output_values = outputs
+            .split(' ')
+            .map(str::parse)
+            .collect::<Result<Vec<SignalPattern>, _>>()?
+            .try_into()
+            .map_err(|_| invalid())?;
+        Ok(Entry {
+            signal_patterns,
+            output_values,
+        })
+    }
+}
+
+/// The step at which decoding an entry failed.
+#[derive(Debug, PartialEq)]
+pub enum DecodeStep {
+    Parse(String),
+    /// A pattern had a length no digit has, or repeated a unique length.
+    UnexpectedPattern {
+        length: usize,
+    },
+    MissingDigit(i8),
+    /// Digits 1 and 4 (for b and d) or 0 and 5 (for c and e) did not differ
+    /// by exactly two segments.
+    SegmentPair(Segment, Segment),
+    Segment(Segment),
+    NoWiring,
+    /// More than one wiring fits an incomplete set of patterns.
+    Ambiguous,
+    UnknownOutput(String),
+    Disagreement {
+        deduced: String,
+        searched: String,
+    },
+}
+
+impl std::fmt::Display for DecodeStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeStep::Parse(err) => write!(f, "{}", err),
+            DecodeStep::UnexpectedPattern { length } => {
+                write!(f, "Unexpected or repeated pattern of length {}", length)
+            }
+            DecodeStep::MissingDigit(digit) => write!(f, "No digit {}", digit),
+            DecodeStep::SegmentPair(first, second) => write!(
+                f,
+                "Cannot separate segments {} and {}",
+                first.to_char(),
+                second.to_char()
+            ),
+            DecodeStep::Segment(segment) => {
+                write!(f, "Cannot deduce segment {}", segment.to_char())
+            }
+            DecodeStep::NoWiring => write!(f, "No wiring lights every digit"),
+            DecodeStep::Ambiguous => write!(f, "Ambiguous, as several wirings fit"),
+            DecodeStep::UnknownOutput(pattern) => {
+                write!(f, "Output value {} matches no digit", pattern)
+            }
+            DecodeStep::Disagreement { deduced, searched } => write!(
+                f,
+                "Deduced wiring {} but search found {}",
+                deduced, searched
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EntryError {
+    pub line_number: usize,
+    pub line: String,
+    pub step: DecodeStep,
+}
+
+impl std::fmt::Display for EntryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {} ({:?}): {}",
+            self.line_number, self.line, self.step
+        )
+    }
+}
+
+/// Decodes entries by deduction, falling back to searching every wiring.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Decoder {
+    /// Also search every wiring, and fail if it disagrees with the deduction.
+    pub cross_check: bool,
+}
+
+/// An entry's wiring and output digits.
+#[derive(Debug)]
+pub struct Decoded {
+    pub solution: WiringSolution,
+    pub digits: [i8; 4],
+}
+
+impl Decoded {
+    /// The output digits read as a four digit number.
+    pub fn value(&self) -> i32 {
+        self.digits
+            .iter()
+            .fold(0, |value, digit| value * 10 + i32::from(*digit))
+    }
+}
+
+impl Decoder {
+    pub fn decode(&self, entry: &Entry) -> Result<Decoded, DecodeStep> {
+        let solution = solve(entry).or_else(|err| {
+            solve_by_search(entry).map_err(|search_err| match search_err {
+                DecodeStep::Ambiguous => search_err,
+                _ => err,
+            })
+        })?;
+        if self.cross_check {
+            let searched = solve_by_search(entry)?;
+            if searched.wiring != solution.wiring {
+                return Err(DecodeStep::Disagreement {
+                    deduced: solution.to_string(),
+                    searched: searched.to_string(),
+                });
+            }
+        }
+        let digits = solution.digits(&entry.output_values)?;
+        Ok(Decoded { solution, digits })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_solve(input: &str) -> [i8; 4] {
+        let entry = input.parse::<Entry>().unwrap();
+        solve(&entry).unwrap().digits(&entry.output_values).unwrap()
+    }
+
+    #[test]
+    fn check_solve() {
+        let inputs: [(&str, [i8;4]);11] = [
+            ("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf", [5,3,5,3]),
+            ("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe", [8,3,9,4]),
+            ("edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc", [9,7,8,1]),
+            ("fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg", [1,1,9,7]),
+            ("fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb", [9,3,6,1]),
+            ("aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea", [4,8,7,3]),
+            ("fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb", [8,4,1,8]),
+            ("dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe", [4,5,4,8]),
+            ("bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef", [1,6,2,5]),
+            ("egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb", [8,7,1,7]),
+            ("gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce", [4,3,1,5]),
+        ];
+        for (input, expectation) in inputs {
+            assert_eq!(get_solve(input), expectation);
+        }
+    }
+
+    #[test]
+    fn check_wiring_solution() {
+        let entry =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"
+                .parse::<Entry>()
+                .unwrap();
+        let solution = solve(&entry).unwrap();
+        assert_eq!(
+            solution.wiring,
+            HashMap::from([
+                (Segment::D, Segment::A),
+                (Segment::E, Segment::B),
+                (Segment::A, Segment::C),
+                (Segment::F, Segment::D),
+                (Segment::G, Segment::E),
+                (Segment::B, Segment::F),
+                (Segment::C, Segment::G),
+            ])
+        );
+        assert_eq!(solution.to_string(), "a->c b->f c->g d->a e->b f->d g->e");
+        assert_eq!(
+            entry_details(1, [5, 3, 0, 3], &solution),
+            "Line 1: 5303 wiring a->c b->f c->g d->a e->b f->d g->e"
+        );
+        assert_eq!(
+            render(&solution, &entry.output_values[..2]),
+            [
+                " dddd    dddd",
+                "e    .  .    a",
+                "e    .  .    a",
+                " ffff    ffff",
+                ".    b  .    b",
+                ".    b  .    b",
+                " cccc    cccc",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            solution.patterns[5].0,
+            "cdfbe".parse::<SignalPattern>().unwrap().0
+        );
+        assert_eq!(
+            solution.digit(&"gcdfa".parse::<SignalPattern>().unwrap()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn check_solve_by_search_matches_solve() {
+        assert_eq!(wirings().len(), 5040);
+        let inputs = [
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+            "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe",
+            "fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg",
+            "gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce",
+        ];
+        for input in inputs {
+            let entry = input.parse::<Entry>().unwrap();
+            let deduced = solve(&entry).unwrap();
+            let searched = solve_by_search(&entry).unwrap();
+            assert_eq!(searched.wiring, deduced.wiring);
+            assert_eq!(
+                searched.digits(&entry.output_values),
+                deduced.digits(&entry.output_values)
+            );
+        }
+
+        let unsolvable = "ab abc abd abcd abcde abcdf abcdef abcdeg abcdefg bcdefg | ab ab ab ab"
+            .parse::<Entry>()
+            .unwrap();
+        assert!(solve_by_search(&unsolvable).is_err());
+    }
+
+    #[test]
+    fn check_decode_errors() {
+        let entry = |input: &str| input.parse::<Entry>().unwrap();
+        let missing_4 =
+            entry("ab abc abcde abcdefg bcdef acdef abcdef bcdefg abdefg acdefg | ab ab ab ab");
+        assert_eq!(solve(&missing_4).err(), Some(DecodeStep::MissingDigit(4)));
+        let repeated_1 =
+            entry("ab ab abc abcd abcde bcdef acdef abcdef bcdefg abcdefg | ab ab ab ab");
+        assert_eq!(
+            solve(&repeated_1).err(),
+            Some(DecodeStep::UnexpectedPattern { length: 2 })
+        );
+        let no_pair =
+            entry("ab abc cdef abcdefg abcde bcdef acdef abcdef bcdefg acdefg | ab ab ab ab");
+        assert_eq!(
+            solve(&no_pair).err(),
+            Some(DecodeStep::SegmentPair(Segment::B, Segment::D))
+        );
+        assert_eq!(
+            Decoder::default().decode(&no_pair).err(),
+            Some(DecodeStep::SegmentPair(Segment::B, Segment::D))
+        );
+
+        let unknown_output = entry(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cb",
+        );
+        assert_eq!(
+            Decoder::default().decode(&unknown_output).err(),
+            Some(DecodeStep::UnknownOutput("bc".to_owned()))
+        );
+
+        let error = EntryError {
+            line_number: 3,
+            line: "ab | cd".to_owned(),
+            step: DecodeStep::MissingDigit(5),
+        };
+        assert_eq!(error.to_string(), "Line 3 (\"ab | cd\"): No digit 5");
+    }
+
+    #[test]
+    fn check_incomplete_patterns() {
+        let complete =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"
+                .parse::<Entry>()
+                .unwrap();
+        let expected = solve(&complete).unwrap();
+
+        // Without digits 5 and 2, which still appear in the output.
+        let incomplete = "acedgfb fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb gcdfa cdbaf"
+            .parse::<Entry>()
+            .unwrap();
+        assert!(solve(&incomplete).is_err());
+        let decoded = Decoder::default().decode(&incomplete).unwrap();
+        assert_eq!(decoded.solution.wiring, expected.wiring);
+        assert_eq!(decoded.digits, [5, 3, 2, 3]);
+        assert_eq!(decoded.value(), 5323);
+
+        let ambiguous = "acedgfb dab eafb ab | ab ab ab ab"
+            .parse::<Entry>()
+            .unwrap();
+        assert_eq!(
+            Decoder::default().decode(&ambiguous).err(),
+            Some(DecodeStep::Ambiguous)
+        );
+
+        assert!("ab | ab ab ab".parse::<Entry>().is_err());
+        assert!("ab ab ab ab ab ab ab ab ab ab ab | ab ab ab ab"
+            .parse::<Entry>()
+            .is_err());
+    }
+}
@@ -0,0 +1,554 @@
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, PartialEq)]
+pub struct Point {
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Which cells count as adjacent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Connectivity {
+    /// Above, below, left and right.
+    Four,
+    /// The diagonals as well.
+    Eight,
+}
+
+/// The cells above, left of, below and right of `p` within the grid.
+fn neighbors4(p: &Point, width: usize, height: usize) -> Vec<Point> {
+    let mut points = Vec::default();
+    if p.row > 0 {
+        points.push(Point {
+            row: p.row - 1,
+            col: p.col,
+        });
+    }
+    if p.col > 0 {
+        points.push(Point {
+            row: p.row,
+            col: p.col - 1,
+        });
+    }
+    if p.row + 1 < height {
+        points.push(Point {
+            row: p.row + 1,
+            col: p.col,
+        });
+    }
+    if p.col + 1 < width {
+        points.push(Point {
+            row: p.row,
+            col: p.col + 1,
+        });
+    }
+    points
+}
+
+/// The four neighbours of `p` followed by its diagonal neighbours.
+fn neighbors8(p: &Point, width: usize, height: usize) -> Vec<Point> {
+    let mut points = neighbors4(p, width, height);
+    for (row_step, col_step) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+        let row = p.row.checked_add_signed(row_step).filter(|r| *r < height);
+        let col = p.col.checked_add_signed(col_step).filter(|c| *c < width);
+        if let (Some(row), Some(col)) = (row, col) {
+            points.push(Point { row, col });
+        }
+    }
+    points
+}
+
+fn get_neighbouring_points(
+    p: &Point,
+    grid: &[Vec<char>],
+    connectivity: Connectivity,
+) -> Vec<Point> {
+    let width = grid.first().map_or(0, |row| row.len());
+    match connectivity {
+        Connectivity::Four => neighbors4(p, width, grid.len()),
+        Connectivity::Eight => neighbors8(p, width, grid.len()),
+    }
+}
+
+fn extract_low_points(grid: &[Vec<char>], connectivity: Connectivity) -> Vec<Point> {
+    let row_range = 0..grid.len();
+    let column_range = 0..grid.first().map_or(0, |row| row.len());
+
+    row_range
+        .clone()
+        .cartesian_product(column_range.clone())
+        .map(|(row, col)| Point { row, col })
+        .filter(|p| {
+            get_neighbouring_points(p, grid, connectivity)
+                .into_iter()
+                .all(|n| grid[n.row][n.col] > grid[p.row][p.col])
+        })
+        .collect()
+}
+
+/// Grows a basin out from a point one ring of neighbours at a time.
+fn get_basin(grid: &[Vec<char>], point: &Point, connectivity: Connectivity) -> Vec<Point> {
+    let mut seen = HashSet::from([(point.row, point.col)]);
+    let mut candidates = vec![Point {
+        row: point.row,
+        col: point.col,
+    }];
+    let mut basin = Vec::default();
+    while !candidates.is_empty() {
+        let next_points = candidates
+            .iter()
+            .flat_map(|c| get_neighbouring_points(c, grid, connectivity))
+            .filter(|n| grid[n.row][n.col] != '9')
+            .filter(|n| seen.insert((n.row, n.col)))
+            .collect_vec();
+
+        basin.append(&mut candidates);
+        candidates = next_points;
+    }
+    basin
+}
+
+/// Disjoint sets of cells, merged by size with path halving.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(count: usize) -> Self {
+        DisjointSet {
+            parent: (0..count).collect(),
+            size: vec![1; count],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (large, small) = if self.size[a] >= self.size[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+    }
+}
+
+/// Labels each cell with its basin, or None for a 9, found in one pass by
+/// joining each cell with its neighbours. Basins are numbered from the
+/// largest down, with their sizes.
+fn label_basins(
+    grid: &[Vec<char>],
+    connectivity: Connectivity,
+) -> (Vec<Vec<Option<usize>>>, Vec<usize>) {
+    let width = grid.first().map_or(0, |row| row.len());
+    let in_basin = |row: usize, col: usize| grid[row][col] != '9';
+
+    let mut sets = DisjointSet::new(grid.len() * width);
+    for (row, col) in (0..grid.len()).cartesian_product(0..width) {
+        if !in_basin(row, col) {
+            continue;
+        }
+        for n in get_neighbouring_points(&Point { row, col }, grid, connectivity) {
+            if in_basin(n.row, n.col) {
+                sets.union(row * width + col, n.row * width + n.col);
+            }
+        }
+    }
+
+    let roots = (0..grid.len())
+        .cartesian_product(0..width)
+        .filter(|(row, col)| in_basin(*row, *col))
+        .map(|(row, col)| row * width + col)
+        .filter(|i| sets.parent[*i] == *i)
+        .sorted_by(|a, b| sets.size[*b].cmp(&sets.size[*a]))
+        .collect::<Vec<_>>();
+    let label_of_root = roots
+        .iter()
+        .enumerate()
+        .map(|(label, root)| (*root, label))
+        .collect::<HashMap<_, _>>();
+
+    let labels = (0..grid.len())
+        .map(|row| {
+            (0..width)
+                .map(|col| in_basin(row, col).then(|| label_of_root[&sets.find(row * width + col)]))
+                .collect()
+        })
+        .collect();
+    (labels, roots.iter().map(|root| sets.size[*root]).collect())
+}
+
+/// The size of every basin, largest first.
+fn basin_sizes(grid: &[Vec<char>], connectivity: Connectivity) -> Vec<usize> {
+    label_basins(grid, connectivity).1
+}
+
+/// Draws the grid with each basin in its own colour, the three largest in
+/// reverse video, and the 9s between them in black.
+fn render(grid: &[Vec<char>], connectivity: Connectivity) -> String {
+    let (labels, _) = label_basins(grid, connectivity);
+    grid.iter()
+        .zip(labels)
+        .map(|(row, row_labels)| {
+            let cells = row
+                .iter()
+                .zip(row_labels)
+                .map(|(height, label)| match label {
+                    None => format!("\x1b[30;40m{}\x1b[0m", height),
+                    Some(label) if label < 3 => {
+                        format!("\x1b[1;7;{}m{}\x1b[0m", 31 + label % 6, height)
+                    }
+                    Some(label) => format!("\x1b[{}m{}\x1b[0m", 31 + label % 6, height),
+                })
+                .collect::<String>();
+            format!("{}\n", cells)
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GridError {
+    /// A row's width differs from the first row's.
+    Ragged {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    NotADigit {
+        row: usize,
+        col: usize,
+        found: char,
+    },
+}
+
+impl std::fmt::Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Ragged {
+                row,
+                width,
+                expected,
+            } => write!(
+                f,
+                "Row {} has {} heights but the first row has {}",
+                row, width, expected
+            ),
+            GridError::NotADigit { row, col, found } => {
+                write!(
+                    f,
+                    "Row {} column {} holds {:?}, not a digit",
+                    row, col, found
+                )
+            }
+        }
+    }
+}
+
+/// Checks every row is the same width and only holds digits. Rows and
+/// columns in errors count from 1.
+fn parse_grid(lines: &[String]) -> Result<Vec<Vec<char>>, GridError> {
+    let expected = lines.first().map_or(0, |line| line.chars().count());
+    lines
+        .iter()
+        .zip(1..)
+        .map(|(line, row)| {
+            let heights = line.chars().collect::<Vec<char>>();
+            if heights.len() != expected {
+                return Err(GridError::Ragged {
+                    row,
+                    width: heights.len(),
+                    expected,
+                });
+            }
+            match heights.iter().zip(1..).find(|(c, _)| !c.is_ascii_digit()) {
+                Some((found, col)) => Err(GridError::NotADigit {
+                    row,
+                    col,
+                    found: *found,
+                }),
+                None => Ok(heights),
+            }
+        })
+        .collect()
+}
+
+/// A parsed height map and the cells it treats as adjacent.
+#[derive(Debug)]
+pub struct HeightMap {
+    grid: Vec<Vec<char>>,
+    connectivity: Connectivity,
+}
+
+impl HeightMap {
+    /// Parses the rows of a height map with up, down, left and right as
+    /// neighbours.
+    pub fn parse(lines: &[String]) -> Result<Self, GridError> {
+        Ok(HeightMap {
+            grid: parse_grid(lines)?,
+            connectivity: Connectivity::Four,
+        })
+    }
+
+    pub fn with_connectivity(self, connectivity: Connectivity) -> Self {
+        HeightMap {
+            connectivity,
+            ..self
+        }
+    }
+
+    pub fn low_points(&self) -> Vec<Point> {
+        extract_low_points(&self.grid, self.connectivity)
+    }
+
+    /// The size of every basin, largest first.
+    pub fn basins(&self) -> Vec<usize> {
+        basin_sizes(&self.grid, self.connectivity)
+    }
+
+    /// The basin sizes found by growing out from each low point.
+    pub fn flood_filled_basins(&self) -> Vec<usize> {
+        self.low_points()
+            .iter()
+            .map(|p| get_basin(&self.grid, p, self.connectivity).len())
+            .sorted_by(|basin_a, basin_b| Ord::cmp(basin_b, basin_a))
+            .collect()
+    }
+
+    /// The sum of one plus the height of each low point.
+    pub fn risk_level(&self) -> u32 {
+        self.low_points()
+            .iter()
+            .filter_map(|p| self.grid[p.row][p.col].to_digit(10))
+            .map(|height| height + 1)
+            .sum()
+    }
+
+    pub fn render(&self) -> String {
+        render(&self.grid, self.connectivity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_sample_grid() -> [Vec<char>; 5] {
+        [
+            "2199943210",
+            "3987894921",
+            "9856789892",
+            "8767896789",
+            "9899965678",
+        ]
+        .map(|s| s.chars().collect::<Vec<char>>())
+    }
+
+    #[test]
+    fn check_extract_low_points() {
+        assert_eq!(
+            extract_low_points(&get_sample_grid(), Connectivity::Four),
+            vec![
+                Point { row: 0, col: 1 },
+                Point { row: 0, col: 9 },
+                Point { row: 2, col: 2 },
+                Point { row: 4, col: 6 },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_get_basin() {
+        let grid = get_sample_grid();
+
+        assert_eq!(
+            get_basin(&grid, &Point { row: 0, col: 1 }, Connectivity::Four),
+            vec![
+                Point { row: 0, col: 1 },
+                Point { row: 0, col: 0 },
+                Point { row: 1, col: 0 },
+            ]
+        );
+
+        assert_eq!(
+            get_basin(&grid, &Point { row: 0, col: 9 }, Connectivity::Four),
+            vec![
+                Point { row: 0, col: 9 },
+                Point { row: 0, col: 8 },
+                Point { row: 1, col: 9 },
+                Point { row: 0, col: 7 },
+                Point { row: 1, col: 8 },
+                Point { row: 2, col: 9 },
+                Point { row: 0, col: 6 },
+                Point { row: 0, col: 5 },
+                Point { row: 1, col: 6 },
+            ]
+        );
+
+        assert_eq!(
+            get_basin(&grid, &Point { row: 2, col: 2 }, Connectivity::Four),
+            vec![
+                Point { row: 2, col: 2 },
+                Point { row: 1, col: 2 },
+                Point { row: 2, col: 1 },
+                Point { row: 3, col: 2 },
+                Point { row: 2, col: 3 },
+                Point { row: 1, col: 3 },
+                Point { row: 3, col: 1 },
+                Point { row: 3, col: 3 },
+                Point { row: 2, col: 4 },
+                Point { row: 1, col: 4 },
+                Point { row: 3, col: 0 },
+                Point { row: 4, col: 1 },
+                Point { row: 3, col: 4 },
+                Point { row: 2, col: 5 },
+            ]
+        );
+
+        assert_eq!(
+            get_basin(&grid, &Point { row: 4, col: 6 }, Connectivity::Four),
+            vec![
+                Point { row: 4, col: 6 },
+                Point { row: 3, col: 6 },
+                Point { row: 4, col: 5 },
+                Point { row: 4, col: 7 },
+                Point { row: 3, col: 7 },
+                Point { row: 4, col: 8 },
+                Point { row: 2, col: 7 },
+                Point { row: 3, col: 8 },
+                Point { row: 4, col: 9 },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_basin_sizes() {
+        let grid = get_sample_grid();
+        assert_eq!(basin_sizes(&grid, Connectivity::Four), vec![14, 9, 9, 3]);
+
+        let flood_filled = extract_low_points(&grid, Connectivity::Four)
+            .iter()
+            .map(|p| get_basin(&grid, p, Connectivity::Four).len())
+            .sorted_by(|a, b| b.cmp(a))
+            .collect::<Vec<_>>();
+        assert_eq!(basin_sizes(&grid, Connectivity::Four), flood_filled);
+        assert_eq!(basin_sizes(&[], Connectivity::Four), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn check_get_basin_on_a_long_basin() {
+        let grid = vec![vec!['1'; 200_000]];
+        assert_eq!(
+            get_basin(&grid, &Point { row: 0, col: 0 }, Connectivity::Four).len(),
+            200_000
+        );
+        assert_eq!(basin_sizes(&grid, Connectivity::Four), vec![200_000]);
+    }
+
+    #[test]
+    fn check_render() {
+        let grid = ["219", "399"].map(|s| s.chars().collect::<Vec<char>>());
+        let (labels, sizes) = label_basins(&grid, Connectivity::Four);
+        assert_eq!(
+            labels,
+            vec![vec![Some(0), Some(0), None], vec![Some(0), None, None]]
+        );
+        assert_eq!(sizes, vec![3]);
+        assert_eq!(
+            render(&grid, Connectivity::Four),
+            "\x1b[1;7;31m2\x1b[0m\x1b[1;7;31m1\x1b[0m\x1b[30;40m9\x1b[0m\n\
+             \x1b[1;7;31m3\x1b[0m\x1b[30;40m9\x1b[0m\x1b[30;40m9\x1b[0m\n"
+        );
+
+        let (labels, _) = label_basins(&get_sample_grid(), Connectivity::Four);
+        assert_eq!(labels[0][..3], [Some(3), Some(3), None]);
+        assert_eq!(labels[2][2], Some(0));
+    }
+
+    #[test]
+    fn check_parse_grid() {
+        let lines = |rows: &[&str]| rows.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            parse_grid(&lines(&["2199943210", "3987894921"])),
+            Ok(get_sample_grid()[..2].to_vec())
+        );
+        assert_eq!(
+            parse_grid(&lines(&["219", "39", "985"])),
+            Err(GridError::Ragged {
+                row: 2,
+                width: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            parse_grid(&lines(&["219", "3x8"])),
+            Err(GridError::NotADigit {
+                row: 2,
+                col: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!(parse_grid(&[]), Ok(vec![]));
+        assert_eq!(extract_low_points(&[], Connectivity::Four), vec![]);
+    }
+
+    #[test]
+    fn check_diagonal_connectivity() {
+        let grid = get_sample_grid();
+        assert_eq!(
+            neighbors8(&Point { row: 0, col: 0 }, 10, 5),
+            vec![
+                Point { row: 1, col: 0 },
+                Point { row: 0, col: 1 },
+                Point { row: 1, col: 1 },
+            ]
+        );
+        assert_eq!(neighbors8(&Point { row: 2, col: 2 }, 10, 5).len(), 8);
+        assert_eq!(
+            extract_low_points(&grid, Connectivity::Eight),
+            extract_low_points(&grid, Connectivity::Four)
+        );
+
+        let flood_filled = extract_low_points(&grid, Connectivity::Eight)
+            .iter()
+            .map(|p| get_basin(&grid, p, Connectivity::Eight).len())
+            .sorted_by(|a, b| b.cmp(a))
+            .collect::<Vec<_>>();
+        assert_eq!(basin_sizes(&grid, Connectivity::Eight), vec![35]);
+        assert_eq!(flood_filled, vec![35, 35, 35, 35]);
+
+        let diagonal = ["191", "919"].map(|s| s.chars().collect::<Vec<char>>());
+        assert_eq!(extract_low_points(&diagonal, Connectivity::Eight).len(), 0);
+        assert_eq!(basin_sizes(&diagonal, Connectivity::Four), vec![1, 1, 1]);
+        assert_eq!(basin_sizes(&diagonal, Connectivity::Eight), vec![3]);
+    }
+
+    #[test]
+    fn check_height_map() {
+        let lines = get_sample_grid()
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        let map = HeightMap::parse(&lines).unwrap();
+        assert_eq!(map.low_points().len(), 4);
+        assert_eq!(map.risk_level(), 15);
+        assert_eq!(map.basins(), vec![14, 9, 9, 3]);
+        assert_eq!(map.flood_filled_basins(), map.basins());
+
+        let map = map.with_connectivity(Connectivity::Eight);
+        assert_eq!(map.basins(), vec![35]);
+        assert!(HeightMap::parse(&["12".to_string(), "3".to_string()]).is_err());
+    }
+}